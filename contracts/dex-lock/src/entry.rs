@@ -1,42 +1,299 @@
 use crate::{
     error::Error,
-    helper::{inputs_contain_owner_cell, position_dex_lock_in_inputs, DexArgs},
+    helper::{
+        inputs_contain_legacy_owner_cell, inputs_contain_owner_cell, is_dex_input, is_group_input,
+        is_order_cancelled, is_order_expired, is_order_repriced, is_order_taken, is_taker_allowed,
+        load_cell_unit_amount, load_counted_run_len, load_dex_args, load_dex_inputs,
+        load_dex_output_args, load_dex_output_total_value, load_unit_amount, parse_udt_amount,
+        payee_lock_hash, positions_dex_lock_in_inputs, DexArgs,
+    },
 };
+use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::prelude::Entity,
-    high_level::{load_cell_capacity, load_cell_data, load_cell_lock, load_cell_type_hash},
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_data_hash, load_cell_lock,
+        load_cell_lock_hash, load_cell_occupied_capacity, load_cell_type_hash, load_input_since,
+        QueryIter,
+    },
+};
+use core::ops::Range;
+use dex_types::{
+    math::mul_div_ceil, since, AcceptedUnit, AmountFormat, AssetCommitment, EnglishAuction,
+    FeeShare, UDT_AMOUNT_LEN,
 };
-
-pub const UDT_AMOUNT_LEN: usize = 16;
 
 pub fn main() -> Result<(), Error> {
-    let args = DexArgs::from_script()?;
+    let args = match load_dex_args() {
+        Ok(args) => args,
+        // The cells locked by version 0 args with trailing bytes before the args were parsed
+        // strictly can no longer be taken, but their owner can still take them back
+        Err(_) if inputs_contain_legacy_owner_cell()? => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    // The bid escrowed in an English auction can only be refunded by a higher bid, so the owner
+    // can no longer cancel the auction after the first bid
+    let cancellable = args
+        .english_auction()
+        .and_then(|auction| auction.bidder_lock_hash)
+        .is_none();
     // When the inputs contain a cell whose lock script is owner, it means that the owner can do
-    // anything including cancel the order
-    if inputs_contain_owner_cell(&args) {
-        return Ok(());
+    // anything including cancel the order, unless the owner has to cancel each order on its own
+    let owner_unlocked =
+        cancellable && !args.is_scoped_cancel() && inputs_contain_owner_cell(&args);
+
+    // Orders with identical args share one script group, so every one of them must be paid for
+    let positions = positions_dex_lock_in_inputs()?;
+    // The members of a bundle share the args, so the whole bundle is the script group and it is
+    // paid for once by the output at the index of its first member
+    if let Some(bundle) = args.bundle() {
+        if !owner_unlocked && positions.len() != bundle.members as usize {
+            return Err(Error::DexBundleIncomplete);
+        }
+    }
+    // The orders paid by the aggregate payment are verified together after the loop
+    let mut aggregate_taken = false;
+    for (position, dex_index) in positions.into_iter().enumerate() {
+        // A reprice marked by the owner is verified even if the owner could do anything else, so
+        // that a wallet cannot change more than the price by mistake
+        if cancellable && is_order_repriced(&args, dex_index)? {
+            verify_reprice(&args, dex_index)?;
+            continue;
+        }
+        if owner_unlocked {
+            continue;
+        }
+        if cancellable && args.is_scoped_cancel() && is_order_cancelled(&args, dex_index)? {
+            continue;
+        }
+        // The owner can still take back a cell that does not match its commitment
+        if let Some(asset) = args.asset() {
+            verify_asset_commitment(&asset, dex_index)?;
+        }
+        if let Some(auction) = args.english_auction() {
+            verify_english_auction(&args, &auction, dex_index)?;
+            continue;
+        }
+        if is_order_expired(&args, dex_index)? {
+            verify_order_return(&args, dex_index)?;
+            continue;
+        }
+        // A private order can only be filled by the taker in the args
+        if let Some(taker_lock_hash) = args.taker_lock_hash() {
+            if !is_taker_allowed(taker_lock_hash, dex_index)? {
+                return Err(Error::TakerNotAllowed);
+            }
+        }
+        if args.is_bid() {
+            verify_bid_delivery(&args, dex_index)?;
+        } else if args.bundle().is_some() && position > 0 {
+            continue;
+        } else if args.is_aggregate_payment() {
+            aggregate_taken = true;
+        } else {
+            verify_order_payment(&args, dex_index)?;
+        }
+    }
+    if aggregate_taken {
+        verify_aggregate_payment(&args)?;
+    }
+
+    Ok(())
+}
+
+// The outputs which the checks of the order at the index may claim: the output at the index, then
+// the leftover order, the royalty, the platform fee or the refund of the previous bidder after it.
+// A payment in counted cells claims the whole run from the index. A share rounded down to zero
+// leaves its output unclaimed, which only makes the range too wide.
+fn claimed_outputs(args: &DexArgs, dex_index: usize) -> Result<Range<usize>, Error> {
+    let following = [
+        args.is_partial_fill(),
+        args.royalty().is_some(),
+        args.platform_fee().is_some(),
+        args.english_auction().is_some(),
+    ]
+    .iter()
+    .filter(|claimed| **claimed)
+    .count();
+    let mut end = dex_index + 1 + following;
+    if args.unit_type_hash.is_some() && args.amount_format() == AmountFormat::CellCount {
+        end = end.max(dex_index + load_counted_run_len(dex_index)?);
     }
+    Ok(dex_index..end)
+}
 
-    // The buyer must pay the specified amount of assets(CKB, UDT, etc.) in the DEX lock script args
-    // to the seller's lock script
-    let dex_index = position_dex_lock_in_inputs()?;
+// The orders with the aggregate payment of the same payee and unit in the transaction, from any
+// script group, are paid together by every output of the payee in the unit. The outputs claimed
+// by the checks of another order are its own payment, shares or return and are not counted, and
+// what the payee spends in the unit is taken from the sum, so that its change cannot pay the
+// orders.
+fn verify_aggregate_payment(args: &DexArgs) -> Result<(), Error> {
+    let payee = payee_lock_hash(args)?;
+    let format = args.amount_format();
+    let reader = args.amount_reader();
+    let mut required_value: u128 = 0;
+    let mut excluded_outputs = Vec::new();
+    for (index, other_args) in load_dex_inputs()? {
+        if !other_args.is_aggregate_payment() || !is_order_taken(&other_args, index)? {
+            excluded_outputs.extend(claimed_outputs(&other_args, index)?);
+            continue;
+        }
+        if other_args.unit_type_hash != args.unit_type_hash
+            || payee_lock_hash(&other_args)? != payee
+        {
+            continue;
+        }
+        // The same unit cannot be read in two ways
+        if other_args.amount_format() != format || other_args.amount_reader() != reader {
+            return Err(Error::UnitTypeNotMatch);
+        }
+        let price = other_args.price(load_input_since(index, Source::Input)?);
+        // A fungible token sold for CKB also pays back the capacity of the DEX cell
+        let capacity = if args.unit_type_hash.is_none() && other_args.is_udt() {
+            load_cell_capacity(index, Source::Input)? as u128
+        } else {
+            0
+        };
+        required_value = required_value
+            .checked_add(price)
+            .and_then(|value| value.checked_add(capacity))
+            .ok_or(Error::TotalValueOverflow)?;
+    }
+
+    let paid_value = sum_payee_value(args, payee, Source::Output, &excluded_outputs)?;
+    let spent_value = sum_payee_value(args, payee, Source::Input, &[])?;
+    if paid_value.saturating_sub(spent_value) < required_value {
+        return Err(Error::TotalValueNotMatch);
+    }
+    Ok(())
+}
+
+// Sums the value in the unit of the order held by the cells of the payee in the source. Every
+// capacity of the payee counts as CKB, and only the cells of the unit type count as the UDT.
+fn sum_payee_value(
+    args: &DexArgs,
+    payee: [u8; 32],
+    source: Source,
+    excluded: &[usize],
+) -> Result<u128, Error> {
+    let mut value: u128 = 0;
+    for (index, lock_hash) in QueryIter::new(load_cell_lock_hash, source).enumerate() {
+        if lock_hash != payee || excluded.contains(&index) {
+            continue;
+        }
+        let type_hash = load_cell_type_hash(index, source)?;
+        let cell_value = match args.unit_type_hash {
+            // A payment in CKB is a plain capacity cell, but the payee spends all its capacity
+            None if source == Source::Input || type_hash.is_none() => {
+                load_cell_capacity(index, source)? as u128
+            }
+            Some(unit_type_hash) if type_hash == Some(unit_type_hash) => {
+                load_cell_unit_amount(args, index, source)?
+            }
+            _ => 0,
+        };
+        value = value
+            .checked_add(cell_value)
+            .ok_or(Error::TotalValueOverflow)?;
+    }
+    Ok(value)
+}
+
+// The owner re-locks the DEX cell at the output with the same index, and only the total_value of
+// the args may change
+fn verify_reprice(args: &DexArgs, dex_index: usize) -> Result<(), Error> {
+    if load_dex_output_total_value(args, dex_index)?.is_none() {
+        return Err(Error::DexRepriceInvalid);
+    }
+    // The new args must be valid, otherwise nobody could unlock the cell again
+    load_dex_output_args(dex_index)?;
+    if load_cell_type_hash(dex_index, Source::Input)?
+        != load_cell_type_hash(dex_index, Source::Output)?
+        || load_cell_data(dex_index, Source::Input)? != load_cell_data(dex_index, Source::Output)?
+        || load_cell_capacity(dex_index, Source::Input)?
+            > load_cell_capacity(dex_index, Source::Output)?
+    {
+        return Err(Error::DexRepriceInvalid);
+    }
+    Ok(())
+}
+
+// The DEX input must be the asset recorded in the args, so that the asset cannot be swapped under
+// the order
+fn verify_asset_commitment(asset: &AssetCommitment, dex_index: usize) -> Result<(), Error> {
+    if load_cell_type_hash(dex_index, Source::Input)? != asset.type_hash
+        || load_cell_data_hash(dex_index, Source::Input)? != asset.data_hash
+    {
+        return Err(Error::DexAssetNotMatch);
+    }
+    Ok(())
+}
+
+// An expired order can no longer be taken, anyone may only return the DEX cell unchanged to the
+// owner_lock at the output with the same index
+fn verify_order_return(args: &DexArgs, dex_index: usize) -> Result<(), Error> {
     let output_lock = load_cell_lock(dex_index, Source::Output)?;
-    if args.owner_lock.as_slice() != output_lock.as_slice() {
-        return Err(Error::DexOwnerLockNotMatch);
+    if args.owner_lock.as_slice() != output_lock.as_slice()
+        || load_cell_type_hash(dex_index, Source::Input)?
+            != load_cell_type_hash(dex_index, Source::Output)?
+        || load_cell_data(dex_index, Source::Input)? != load_cell_data(dex_index, Source::Output)?
+        || load_cell_capacity(dex_index, Source::Input)?
+            > load_cell_capacity(dex_index, Source::Output)?
+    {
+        return Err(Error::DexOrderExpired);
     }
+    Ok(())
+}
+
+// The buyer must pay the specified amount of assets(CKB, UDT, etc.) in the DEX lock script args
+// to the seller's lock script. A fungible token sold for CKB also pays back the capacity of the DEX
+// cell, or of every member of a bundle.
+fn verify_order_payment(args: &DexArgs, dex_index: usize) -> Result<(), Error> {
+    verify_receiver(args, dex_index)?;
+    let unit = select_unit(args, dex_index)?;
 
-    if let Some(unit_type_hash) = args.unit_type_hash {
+    if let Some(nft_type_hash) = args.nft_type_hash() {
+        verify_nft_sale(&unit, nft_type_hash, dex_index)?;
+    }
+
+    let partial_value = if args.is_partial_fill() {
+        verify_partial_fill(args, dex_index)?
+    } else {
+        None
+    };
+    // The price of a Dutch auction is proven by the since of the DEX input, and an auction
+    // accepts no other unit
+    let paid_value = match partial_value {
+        Some(value) => value,
+        None if unit.unit_type_hash == args.unit_type_hash => {
+            args.price(load_input_since(dex_index, Source::Input)?)
+        }
+        None => unit.total_value,
+    };
+
+    // The shares follow the payment output, or the leftover DEX cell if the order is partially
+    // filled, and the seller receives the rest of the paid value. The platform fee follows the
+    // royalty output if it exists.
+    let mut share_index = dex_index + if partial_value.is_some() { 2 } else { 1 };
+    let mut total_value = paid_value;
+    for share in [args.royalty(), args.platform_fee()].iter().flatten() {
+        let share_value = share.amount(paid_value);
+        if verify_share_output(args, &unit, share, share_value, share_index)? {
+            share_index += 1;
+        }
+        total_value -= share_value;
+    }
+
+    if let Some(unit_type_hash) = unit.unit_type_hash {
         if let Some(type_hash) = load_cell_type_hash(dex_index, Source::Output)? {
             if type_hash != unit_type_hash {
                 return Err(Error::UnitTypeNotMatch);
             } else {
-                let mut buf = [0u8; UDT_AMOUNT_LEN];
-                let data = load_cell_data(dex_index, Source::Output).unwrap();
-                // not check the data length, because it's checked by xudt script
-                buf.copy_from_slice(&data);
-                let amount = u128::from_le_bytes(buf);
-                if amount < args.total_value {
+                // xUDT cells may carry extension data after the amount, and other formats are
+                // read as the args tell or by the amount reader of the order
+                let amount = load_unit_amount(args, dex_index)?;
+                if amount < total_value {
                     return Err(Error::TotalValueNotMatch);
                 }
             }
@@ -44,17 +301,25 @@ pub fn main() -> Result<(), Error> {
             return Err(Error::UnitTypeNotMatch);
         }
     } else {
-        let dex_input_capacity = load_cell_capacity(dex_index, Source::Input)? as u128;
+        // The capacity of a partially filled DEX cell stays in the leftover DEX cell
+        let dex_input_capacity = if partial_value.is_some() {
+            0
+        } else if args.bundle().is_some() {
+            QueryIter::new(load_cell_capacity, Source::GroupInput)
+                .map(|capacity| capacity as u128)
+                .sum()
+        } else {
+            load_cell_capacity(dex_index, Source::Input)? as u128
+        };
         let output_capacity = load_cell_capacity(dex_index, Source::Output)? as u128;
 
         if args.is_nft() {
-            if args.total_value > output_capacity {
+            if total_value > output_capacity {
                 return Err(Error::DexNFTTotalValueNotMatch);
             }
         } else if args.is_udt() {
             // Prevent total_value(u128) from overflowing
-            let total_capacity = args
-                .total_value
+            let total_capacity = total_value
                 .checked_add(dex_input_capacity)
                 .ok_or(Error::TotalValueOverflow)?;
             if total_capacity > output_capacity {
@@ -65,3 +330,286 @@ pub fn main() -> Result<(), Error> {
 
     Ok(())
 }
+
+// An order with alternative units is paid in the unit of the type of the output at the index, or
+// in CKB if the output has no type. Any other order is paid in its own unit.
+fn select_unit(args: &DexArgs, dex_index: usize) -> Result<AcceptedUnit, Error> {
+    let units = args.accepted_units();
+    if units.len() == 1 {
+        return Ok(units[0]);
+    }
+    let type_hash = load_cell_type_hash(dex_index, Source::Output)?;
+    units
+        .into_iter()
+        .find(|unit| unit.unit_type_hash == type_hash)
+        .ok_or(Error::UnitTypeNotMatch)
+}
+
+// After the deadline the auction is settled: the top bid goes to the seller and the asset to the
+// top bidder, or the asset is returned to the owner if nobody bid. Nothing proves that a
+// transaction is committed before the deadline, so a higher bid is accepted until the settlement.
+fn verify_english_auction(
+    args: &DexArgs,
+    auction: &EnglishAuction,
+    dex_index: usize,
+) -> Result<(), Error> {
+    if !since::is_expired(
+        auction.deadline,
+        load_input_since(dex_index, Source::Input)?,
+    ) {
+        return verify_outbid(args, auction, dex_index);
+    }
+    match auction.bidder_lock_hash {
+        Some(bidder_lock_hash) => verify_auction_settlement(args, bidder_lock_hash, dex_index),
+        None => verify_order_return(args, dex_index),
+    }
+}
+
+// A higher bid replaces the top bid: the DEX cell is re-locked at the same index with only the bid
+// and the bidder changed, its escrow is replaced by the new bid, and the previous bidder is
+// refunded at the next output
+fn verify_outbid(args: &DexArgs, auction: &EnglishAuction, dex_index: usize) -> Result<(), Error> {
+    let output_args = load_dex_output_args(dex_index)?.ok_or(Error::DexAuctionBidInvalid)?;
+    let output_auction = output_args
+        .english_auction()
+        .filter(|output_auction| output_auction.bidder_lock_hash.is_some())
+        .ok_or(Error::DexAuctionBidInvalid)?;
+    let mut expected_args = output_args.clone();
+    expected_args.total_value = args.total_value;
+    if let Some(extension) = expected_args.extension.as_mut() {
+        extension.english_auction = Some(*auction);
+    }
+    if expected_args != *args || output_auction.deadline != auction.deadline {
+        return Err(Error::DexAuctionBidInvalid);
+    }
+
+    // The first bid must reach the reserve price and a later one must exceed the top bid
+    let min_bid = if auction.bidder_lock_hash.is_some() {
+        args.total_value
+            .checked_add(1)
+            .ok_or(Error::TotalValueOverflow)?
+    } else {
+        args.total_value
+    };
+    if output_args.total_value < min_bid {
+        return Err(Error::DexAuctionBidInvalid);
+    }
+
+    let required_capacity =
+        load_capacity_besides_escrow(auction.escrow(args.total_value), dex_index)?
+            .checked_add(output_args.total_value)
+            .ok_or(Error::TotalValueOverflow)?;
+    if load_cell_type_hash(dex_index, Source::Input)?
+        != load_cell_type_hash(dex_index, Source::Output)?
+        || load_cell_data(dex_index, Source::Input)? != load_cell_data(dex_index, Source::Output)?
+        || (load_cell_capacity(dex_index, Source::Output)? as u128) < required_capacity
+    {
+        return Err(Error::DexAuctionBidInvalid);
+    }
+
+    if let Some(previous_bidder) = auction.bidder_lock_hash {
+        let refund_index = dex_index + 1;
+        // The output of another order at the index would be counted twice
+        if is_dex_input(refund_index)? {
+            return Err(Error::DexOutputIndexConflict);
+        }
+        if load_cell_lock_hash(refund_index, Source::Output)? != previous_bidder
+            || (load_cell_capacity(refund_index, Source::Output)? as u128) < args.total_value
+        {
+            return Err(Error::DexAuctionRefundNotMatch);
+        }
+    }
+    Ok(())
+}
+
+// The seller receives the top bid at the output with the same index and the winner receives the
+// asset unchanged at the next output, along with the capacity of the DEX cell besides the escrow
+fn verify_auction_settlement(
+    args: &DexArgs,
+    bidder_lock_hash: [u8; 32],
+    dex_index: usize,
+) -> Result<(), Error> {
+    verify_receiver(args, dex_index)?;
+    if (load_cell_capacity(dex_index, Source::Output)? as u128) < args.total_value {
+        return Err(Error::TotalValueNotMatch);
+    }
+
+    let winner_index = dex_index + 1;
+    if is_dex_input(winner_index)? {
+        return Err(Error::DexOutputIndexConflict);
+    }
+    let asset_capacity = load_capacity_besides_escrow(args.total_value, dex_index)?;
+    if load_cell_lock_hash(winner_index, Source::Output)? != bidder_lock_hash
+        || load_cell_type_hash(dex_index, Source::Input)?
+            != load_cell_type_hash(winner_index, Source::Output)?
+        || load_cell_data(dex_index, Source::Input)?
+            != load_cell_data(winner_index, Source::Output)?
+        || (load_cell_capacity(winner_index, Source::Output)? as u128) < asset_capacity
+    {
+        return Err(Error::DexAuctionWinnerNotMatch);
+    }
+    Ok(())
+}
+
+// A bid only counts if the DEX cell escrows it on top of the capacity occupied by the asset,
+// otherwise an auction could be listed with a fake bidder and no escrow
+fn load_capacity_besides_escrow(escrow: u128, dex_index: usize) -> Result<u128, Error> {
+    let capacity = load_cell_capacity(dex_index, Source::Input)? as u128;
+    let occupied_capacity = load_cell_occupied_capacity(dex_index, Source::Input)? as u128;
+    if capacity
+        < occupied_capacity
+            .checked_add(escrow)
+            .ok_or(Error::TotalValueOverflow)?
+    {
+        return Err(Error::DexAuctionInvalid);
+    }
+    Ok(capacity - escrow)
+}
+
+// The taker of a bid delivers the wanted asset to the seller's lock script at the output with the
+// same index and takes the payment locked in the DEX cell
+fn verify_bid_delivery(args: &DexArgs, dex_index: usize) -> Result<(), Error> {
+    verify_receiver(args, dex_index)?;
+
+    if load_cell_type_hash(dex_index, Source::Output)? != args.bid_type_hash() {
+        return Err(Error::UnitTypeNotMatch);
+    }
+    // An NFT is identified by its type hash alone, and a fungible token must be delivered in the
+    // amount of total_value
+    if args.is_udt() {
+        let amount = parse_udt_amount(&load_cell_data(dex_index, Source::Output)?)?;
+        if amount < args.total_value {
+            return Err(Error::TotalValueNotMatch);
+        }
+    }
+    Ok(())
+}
+
+// The output at the index goes to the receiver_lock if it exists, otherwise to the owner_lock
+fn verify_receiver(args: &DexArgs, dex_index: usize) -> Result<(), Error> {
+    if let Some(receiver_lock) = args.receiver_lock {
+        if load_cell_lock_hash(dex_index, Source::Output)? != receiver_lock {
+            return Err(Error::DexReceiverLockNotMatch);
+        }
+    } else {
+        let output_lock = load_cell_lock(dex_index, Source::Output)?;
+        if args.owner_lock.as_slice() != output_lock.as_slice() {
+            return Err(Error::DexOwnerLockNotMatch);
+        }
+    }
+    Ok(())
+}
+
+// A share of the paid value goes to the output at the index in the unit of the payment, and
+// a share rounded down to zero needs no output. Returns true if the output is taken by the share.
+fn verify_share_output(
+    args: &DexArgs,
+    unit: &AcceptedUnit,
+    share: &FeeShare,
+    value: u128,
+    index: usize,
+) -> Result<bool, Error> {
+    if value == 0 {
+        return Ok(false);
+    }
+    // The output of another order at the index would be counted twice, but the other members of
+    // a bundle have no output of their own
+    if is_dex_input(index)? && !(args.bundle().is_some() && is_group_input(index)?) {
+        return Err(Error::DexOutputIndexConflict);
+    }
+    if load_cell_lock_hash(index, Source::Output)? != share.lock_hash {
+        return Err(Error::DexShareLockNotMatch);
+    }
+
+    let output_value = if let Some(unit_type_hash) = unit.unit_type_hash {
+        if load_cell_type_hash(index, Source::Output)? != Some(unit_type_hash) {
+            return Err(Error::UnitTypeNotMatch);
+        }
+        load_unit_amount(args, index)?
+    } else {
+        load_cell_capacity(index, Source::Output)? as u128
+    };
+    if output_value < value {
+        return Err(Error::DexShareValueNotMatch);
+    }
+    Ok(true)
+}
+
+// The listed cell must be the NFT recorded in the args and leave to exactly one output, and an
+// NFT sold for CKB must be paid by a plain capacity cell
+fn verify_nft_sale(
+    unit: &AcceptedUnit,
+    nft_type_hash: [u8; 32],
+    dex_index: usize,
+) -> Result<(), Error> {
+    if load_cell_type_hash(dex_index, Source::Input)? != Some(nft_type_hash) {
+        return Err(Error::DexNFTTypeNotMatch);
+    }
+    let nft_outputs = QueryIter::new(load_cell_type_hash, Source::Output)
+        .filter(|type_hash| type_hash == &Some(nft_type_hash))
+        .count();
+    if nft_outputs != 1 {
+        return Err(Error::DexNFTTypeNotMatch);
+    }
+
+    if unit.unit_type_hash.is_none()
+        && (load_cell_type_hash(dex_index, Source::Output)?.is_some()
+            || !load_cell_data(dex_index, Source::Output)?.is_empty())
+    {
+        return Err(Error::DexNFTPaymentInvalid);
+    }
+    Ok(())
+}
+
+// A partially filled order re-locks the remaining tokens in a new DEX cell right after the paid
+// output, and the total_value of the new DEX cell is reduced in proportion to the sold tokens.
+// Returns the value to pay for the sold tokens, or None if the order is filled entirely.
+fn verify_partial_fill(args: &DexArgs, dex_index: usize) -> Result<Option<u128>, Error> {
+    let leftover_index = dex_index + 1;
+    let leftover_total_value = match load_dex_output_total_value(args, leftover_index)? {
+        Some(total_value) => total_value,
+        None => return Ok(None),
+    };
+
+    // The leftover DEX cell must keep the asset type, the extra data and the capacity
+    let asset_type_hash = load_cell_type_hash(dex_index, Source::Input)?;
+    if asset_type_hash.is_none()
+        || asset_type_hash != load_cell_type_hash(leftover_index, Source::Output)?
+    {
+        return Err(Error::DexPartialFillInvalid);
+    }
+    if load_cell_capacity(leftover_index, Source::Output)?
+        < load_cell_capacity(dex_index, Source::Input)?
+    {
+        return Err(Error::DexPartialFillInvalid);
+    }
+    let input_data = load_cell_data(dex_index, Source::Input)?;
+    let leftover_data = load_cell_data(leftover_index, Source::Output)?;
+    if input_data.len() < UDT_AMOUNT_LEN
+        || leftover_data.len() < UDT_AMOUNT_LEN
+        || input_data[UDT_AMOUNT_LEN..] != leftover_data[UDT_AMOUNT_LEN..]
+    {
+        return Err(Error::DexPartialFillInvalid);
+    }
+
+    let input_amount = parse_udt_amount(&input_data)?;
+    let leftover_amount = parse_udt_amount(&leftover_data)?;
+    if leftover_amount == 0 || leftover_amount >= input_amount {
+        return Err(Error::DexPartialFillAmountInvalid);
+    }
+
+    // The sold tokens are paid at the order price rounded up in favour of the seller
+    let paid_value = mul_div_ceil(
+        args.total_value,
+        input_amount - leftover_amount,
+        input_amount,
+    )
+    .ok_or(Error::TotalValueOverflow)?;
+    if paid_value >= args.total_value {
+        return Err(Error::DexPartialFillAmountInvalid);
+    }
+    if leftover_total_value != args.total_value - paid_value {
+        return Err(Error::DexPartialFillTotalValueNotMatch);
+    }
+    Ok(Some(paid_value))
+}
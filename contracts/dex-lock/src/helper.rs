@@ -1,7 +1,20 @@
+use alloc::{ffi::CString, format, vec::Vec};
 use ckb_std::{
     ckb_constants::Source,
-    ckb_types::{bytes::Bytes, packed::Script, prelude::*},
-    high_level::{load_cell_lock, load_script, QueryIter},
+    ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*},
+    error::SysError,
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_lock, load_cell_lock_hash,
+        load_cell_type_hash, load_input_since, load_script, load_witness_args, spawn_cell,
+        QueryIter,
+    },
+    syscalls::{close, pipe, read, wait},
+};
+
+pub use dex_types::DexArgs;
+use dex_types::{
+    since, witness, AmountFormat, AmountReader, READER_SOURCE_INPUT, READER_SOURCE_OUTPUT,
+    UDT_AMOUNT_LEN,
 };
 
 use crate::error::Error;
@@ -10,96 +23,403 @@ pub fn parse_array<const N: usize>(arr: &[u8]) -> Result<[u8; N], Error> {
     arr.try_into().map_err(|_| Error::Encoding)
 }
 
-const MIN_ARGS_SIZE: usize = 66;
-#[derive(Debug, Clone)]
-pub struct DexArgs {
-    // the minimum length of serialized lock script is 49bytes
-    pub owner_lock:     Script,
-    // 0b_xxxx_x0xx is for FT asset and 0b_xxxx_x1xx is for NFT asset
-    pub setup:          u8,
-    pub total_value:    u128,
-    // Optional
-    pub receiver_lock:  Option<[u8; 32]>,
-    pub unit_type_hash: Option<[u8; 32]>,
-}
-
-impl DexArgs {
-    pub fn from_script() -> Result<Self, Error> {
-        let data: Bytes = load_script()?.args().unpack();
-        if data.len() < MIN_ARGS_SIZE {
-            return Err(Error::LockArgsInvalid);
+// The UDT amount is the first 16 bytes of the cell data in little endian, and the data after it is
+// ignored because xUDT cells may carry extension data there
+pub fn parse_udt_amount(data: &[u8]) -> Result<u128, Error> {
+    if data.len() < UDT_AMOUNT_LEN {
+        return Err(Error::UnitDataLengthNotEnough);
+    }
+    Ok(u128::from_le_bytes(parse_array(&data[..UDT_AMOUNT_LEN])?))
+}
+
+// Returns the amount paid in the unit of the order by the output at the index. Counted cells are
+// summed over the run of outputs with the same lock and type, which stops before the payment of
+// another order.
+pub fn load_unit_amount(args: &DexArgs, index: usize) -> Result<u128, Error> {
+    if args.amount_format() != AmountFormat::CellCount {
+        return load_cell_unit_amount(args, index, Source::Output);
+    }
+    Ok(load_counted_run_len(index)? as u128)
+}
+
+// Returns the length of the run of outputs from the index with the lock and the type of the output
+// at the index, which stops before the output at the index of a DEX input. Zero if the output does
+// not exist.
+pub fn load_counted_run_len(index: usize) -> Result<usize, Error> {
+    let lock_hash = match load_cell_lock_hash(index, Source::Output) {
+        Ok(lock_hash) => lock_hash,
+        Err(SysError::IndexOutOfBound) => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+    let type_hash = load_cell_type_hash(index, Source::Output)?;
+    let mut len = 1;
+    for next in index + 1.. {
+        if is_dex_input(next)? {
+            break;
         }
-        let owner_size = u32::from_le_bytes(parse_array::<4>(&data[0..4])?) as usize;
-        let required_size = owner_size + 17;
-        if data.len() < required_size {
-            return Err(Error::LockArgsInvalid);
+        match load_cell_lock_hash(next, Source::Output) {
+            Ok(next_lock_hash) if next_lock_hash == lock_hash => {}
+            Ok(_) | Err(SysError::IndexOutOfBound) => break,
+            Err(err) => return Err(err.into()),
         }
+        if load_cell_type_hash(next, Source::Output)? != type_hash {
+            break;
+        }
+        len += 1;
+    }
+    Ok(len)
+}
 
-        let owner_lock = Script::from_slice(&data[..owner_size]).map_err(|_e| Error::Encoding)?;
-        let setup = data[owner_size];
+// Returns the amount held by one cell of the unit type, read by the amount reader of the order if
+// it has one, otherwise in its amount format
+pub fn load_cell_unit_amount(args: &DexArgs, index: usize, source: Source) -> Result<u128, Error> {
+    match args.amount_reader() {
+        Some(reader) => spawn_amount_reader(&reader, index, source),
+        None => args
+            .amount_format()
+            .amount(&load_cell_data(index, source)?)
+            .ok_or(Error::UnitDataLengthNotEnough),
+    }
+}
 
-        if setup > 0b0000_0111 {
-            return Err(Error::DexSetupInvalid);
-        }
-        let total_value =
-            u128::from_be_bytes(parse_array::<16>(&data[owner_size + 1..required_size])?);
+// The reader is spawned from the cell deps with the index and the source of the cell in argv and
+// the write end of a pipe, and must write the amount as a little-endian u128 and exit with 0
+fn spawn_amount_reader(reader: &AmountReader, index: usize, source: Source) -> Result<u128, Error> {
+    let hash_type = match reader.hash_type {
+        0 => ScriptHashType::Data,
+        1 => ScriptHashType::Type,
+        2 => ScriptHashType::Data1,
+        4 => ScriptHashType::Data2,
+        _ => return Err(Error::DexSetupInvalid),
+    };
+    let source = match source {
+        Source::Input => READER_SOURCE_INPUT,
+        Source::Output => READER_SOURCE_OUTPUT,
+        _ => return Err(Error::DexAmountReaderFailed),
+    };
+    let index = CString::new(format!("{}", index)).map_err(|_| Error::Encoding)?;
+    let source = CString::new(source).map_err(|_| Error::Encoding)?;
 
-        let receiver_lock_flag: bool = (setup & 0b0000_0001) != 0;
+    let (read_fd, write_fd) = pipe()?;
+    // The write end is moved to the reader
+    let pid = match spawn_cell(
+        &reader.code_hash,
+        hash_type,
+        &[index.as_c_str(), source.as_c_str()],
+        &[write_fd],
+    ) {
+        Ok(pid) => pid,
+        Err(SysError::IndexOutOfBound) => return Err(Error::DexAmountReaderMissing),
+        Err(err) => return Err(err.into()),
+    };
 
-        // Not support receiver_lock now
-        if receiver_lock_flag {
-            return Err(Error::DexSetupInvalid);
+    let mut amount = [0u8; UDT_AMOUNT_LEN];
+    let mut len = 0;
+    while len < UDT_AMOUNT_LEN {
+        match read(read_fd, &mut amount[len..]) {
+            Ok(0) | Err(SysError::OtherEndClosed) => break,
+            Ok(n) => len += n,
+            Err(err) => return Err(err.into()),
         }
+    }
+    close(read_fd)?;
+    if wait(pid)? != 0 || len < UDT_AMOUNT_LEN {
+        return Err(Error::DexAmountReaderFailed);
+    }
+    Ok(u128::from_le_bytes(amount))
+}
+
+pub fn load_dex_args() -> Result<DexArgs, Error> {
+    let data: Bytes = load_script()?.args().unpack();
+    Ok(DexArgs::from_slice(&data)?)
+}
 
-        let unit_type_hash_flag: bool = (setup & 0b0000_0010) != 0;
+// Returns the total_value of the output cell if its lock is a DEX lock whose args only differ
+// from the current one in total_value
+pub fn load_dex_output_total_value(args: &DexArgs, index: usize) -> Result<Option<u128>, Error> {
+    let output_lock = match load_cell_lock(index, Source::Output) {
+        Ok(lock) => lock,
+        Err(SysError::IndexOutOfBound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let current_lock = load_script()?;
+    if output_lock.code_hash().as_slice() != current_lock.code_hash().as_slice()
+        || output_lock.hash_type() != current_lock.hash_type()
+    {
+        return Ok(None);
+    }
+    let current_args: Bytes = current_lock.args().unpack();
+    let output_args: Bytes = output_lock.args().unpack();
+    let pos = args.total_value_pos();
+    if output_args.len() != current_args.len()
+        || output_args[..pos] != current_args[..pos]
+        || output_args[pos + 16..] != current_args[pos + 16..]
+    {
+        return Ok(None);
+    }
+    let total_value = u128::from_be_bytes(parse_array::<16>(&output_args[pos..pos + 16])?);
+    Ok(Some(total_value))
+}
 
-        // if receiver_lock_flag exists, unit_type_hash_pos should be owner_size + 49
-        let unit_type_hash_pos = owner_size + 17;
+// Returns the args of the output cell if its lock is a DEX lock of the same code
+pub fn load_dex_output_args(index: usize) -> Result<Option<DexArgs>, Error> {
+    let output_lock = match load_cell_lock(index, Source::Output) {
+        Ok(lock) => lock,
+        Err(SysError::IndexOutOfBound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let current_lock = load_script()?;
+    if output_lock.code_hash().as_slice() != current_lock.code_hash().as_slice()
+        || output_lock.hash_type() != current_lock.hash_type()
+    {
+        return Ok(None);
+    }
+    let output_args: Bytes = output_lock.args().unpack();
+    Ok(Some(DexArgs::from_slice(&output_args)?))
+}
 
-        let unit_type_hash = match Self::get_hash(&data, unit_type_hash_flag, unit_type_hash_pos) {
-            Ok(data) => data,
-            Err(e) => return Err(e),
-        };
+// Returns true if the input at the index is an order of a DEX lock, whose payment is the output
+// at the same index
+pub fn is_dex_input(index: usize) -> Result<bool, Error> {
+    let input_lock = match load_cell_lock(index, Source::Input) {
+        Ok(lock) => lock,
+        Err(SysError::IndexOutOfBound) => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let current_lock = load_script()?;
+    Ok(
+        input_lock.code_hash().as_slice() == current_lock.code_hash().as_slice()
+            && input_lock.hash_type() == current_lock.hash_type(),
+    )
+}
 
-        Ok(DexArgs {
-            owner_lock,
-            setup,
-            total_value,
-            receiver_lock: None,
-            unit_type_hash,
+// Returns the args of every order of a DEX lock in the inputs with its index, including the ones
+// of other script groups
+pub fn load_dex_inputs() -> Result<Vec<(usize, DexArgs)>, Error> {
+    let current_lock = load_script()?;
+    QueryIter::new(load_cell_lock, Source::Input)
+        .enumerate()
+        .filter(|(_, lock)| {
+            lock.code_hash().as_slice() == current_lock.code_hash().as_slice()
+                && lock.hash_type() == current_lock.hash_type()
         })
-    }
+        .map(|(index, lock)| {
+            let args: Bytes = lock.args().unpack();
+            Ok((index, DexArgs::from_slice(&args)?))
+        })
+        .collect()
+}
 
-    fn get_hash(data: &Bytes, flag: bool, from_pos: usize) -> Result<Option<[u8; 32]>, Error> {
-        if !flag {
-            return Ok(None);
-        }
-        if data.len() < from_pos + 32 {
-            Err(Error::LockArgsInvalid)
-        } else {
-            let mut temp = [0u8; 32];
-            temp.copy_from_slice(&data[from_pos..from_pos + 32]);
-            Ok(Some(temp))
-        }
+// The lock script hash of the payee, the receiver_lock if it exists, otherwise the hash of the
+// owner_lock
+pub fn payee_lock_hash(args: &DexArgs) -> Result<[u8; 32], Error> {
+    if let Some(receiver_lock) = args.receiver_lock {
+        return Ok(receiver_lock);
     }
+    let owner_lock = Script::from_slice(&args.owner_lock).map_err(|_| Error::Encoding)?;
+    Ok(owner_lock.calc_script_hash().unpack())
+}
 
-    pub fn is_udt(&self) -> bool {
-        self.setup & 0b0000_0100 == 0
+// Returns true if the input at the index has the same lock script, which makes it a member of the
+// same bundle
+pub fn is_group_input(index: usize) -> Result<bool, Error> {
+    let input_lock = match load_cell_lock(index, Source::Input) {
+        Ok(lock) => lock,
+        Err(SysError::IndexOutOfBound) => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(input_lock.as_slice() == load_script()?.as_slice())
+}
+
+// Every input of the script group is an order on its own, so the indexes of all of them are
+// returned and each one must be settled by the output at the same index
+pub fn positions_dex_lock_in_inputs() -> Result<Vec<usize>, Error> {
+    let current_lock = load_script()?;
+    let positions: Vec<usize> = QueryIter::new(load_cell_lock, Source::Input)
+        .enumerate()
+        .filter(|(_, lock)| lock.as_slice() == current_lock.as_slice())
+        .map(|(index, _)| index)
+        .collect();
+    if positions.is_empty() {
+        return Err(Error::IndexOutOfBound);
     }
+    Ok(positions)
+}
 
-    pub fn is_nft(&self) -> bool {
-        self.setup & 0b0000_0100 != 0
+// The since of the DEX input proves that the transaction is committed after the expiry, and
+// nothing can prove the opposite, so an order without it is treated as not expired. The expiry
+// is a return path for the owner and keepers, not a deadline that takers must prove
+pub fn is_order_expired(args: &DexArgs, dex_index: usize) -> Result<bool, Error> {
+    match args.expiry() {
+        Some(expiry) => Ok(since::is_expired(
+            expiry,
+            load_input_since(dex_index, Source::Input)?,
+        )),
+        None => Ok(false),
     }
 }
 
-pub fn position_dex_lock_in_inputs() -> Result<usize, Error> {
-    let current_lock = load_script()?;
-    QueryIter::new(load_cell_lock, Source::Input)
-        .position(|lock| lock.as_slice() == current_lock.as_slice())
-        .ok_or(Error::IndexOutOfBound)
+// Returns true if the args are the legacy version 0 args with trailing bytes and an input is
+// locked by their owner lock
+pub fn inputs_contain_legacy_owner_cell() -> Result<bool, Error> {
+    let data: Bytes = load_script()?.args().unpack();
+    Ok(DexArgs::from_legacy_slice(&data).is_ok_and(|args| inputs_contain_owner_cell(&args)))
 }
 
 pub fn inputs_contain_owner_cell(args: &DexArgs) -> bool {
     QueryIter::new(load_cell_lock, Source::Input)
         .any(|lock| lock.as_slice() == args.owner_lock.as_slice())
 }
+
+// The lock field of the witness of the DEX input, where the owner marks the order
+fn load_witness_lock(dex_index: usize) -> Result<Option<Bytes>, Error> {
+    match load_witness_args(dex_index, Source::Input) {
+        Ok(witness_args) => Ok(witness_args.lock().to_opt().map(|lock| lock.unpack())),
+        Err(SysError::IndexOutOfBound) | Err(SysError::Encoding) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// The owner proves a mark by an input locked by the owner_lock in the same transaction
+fn verify_owner_mark(args: &DexArgs, owner_lock_hash: &[u8]) -> Result<(), Error> {
+    let owner_index = QueryIter::new(load_cell_lock, Source::Input)
+        .position(|lock| lock.as_slice() == args.owner_lock.as_slice())
+        .ok_or(Error::DexCancelNotAuthorized)?;
+    if load_cell_lock_hash(owner_index, Source::Input)? != owner_lock_hash {
+        return Err(Error::DexCancelNotAuthorized);
+    }
+    Ok(())
+}
+
+// A scoped cancel marks the DEX input with the owner lock hash in the lock field of its witness,
+// and the owner proves it by an input locked by the owner_lock in the same transaction
+pub fn is_order_cancelled(args: &DexArgs, dex_index: usize) -> Result<bool, Error> {
+    let owner_lock_hash = match load_witness_lock(dex_index)? {
+        Some(lock) => lock,
+        None => return Ok(false),
+    };
+    verify_owner_mark(args, &owner_lock_hash)?;
+    Ok(true)
+}
+
+// A reprice marks the DEX input with the owner lock hash followed by the reprice byte, and any
+// other witness is not a reprice
+pub fn is_order_repriced(args: &DexArgs, dex_index: usize) -> Result<bool, Error> {
+    let owner_lock_hash = match load_witness_lock(dex_index)?
+        .as_deref()
+        .and_then(witness::parse_reprice_mark)
+    {
+        Some(owner_lock_hash) => owner_lock_hash,
+        None => return Ok(false),
+    };
+    verify_owner_mark(args, &owner_lock_hash)?;
+    Ok(true)
+}
+
+// Returns true if the order at the index is taken in the transaction instead of being repriced,
+// cancelled or returned to the owner, which its own script group verifies. An order with the
+// aggregate payment or a taker lock is never an English auction, so the owner can always cancel it.
+pub fn is_order_taken(args: &DexArgs, index: usize) -> Result<bool, Error> {
+    let cancelled = if args.is_scoped_cancel() {
+        is_order_cancelled(args, index)?
+    } else {
+        inputs_contain_owner_cell(args)
+    };
+    Ok(!(is_order_repriced(args, index)? || cancelled || is_order_expired(args, index)?))
+}
+
+// The whole asset of the DEX input which the taker of a private order receives. A fungible token
+// must be received in the sold amount, which is the amount of the DEX input unless a leftover order
+// keeps the rest, and any other asset with the data of the DEX input.
+struct TakerAsset {
+    type_hash:   Option<[u8; 32]>,
+    data:        Vec<u8>,
+    sold_amount: Option<u128>,
+    capacity:    u64,
+}
+
+impl TakerAsset {
+    fn load(args: &DexArgs, dex_index: usize) -> Result<Self, Error> {
+        let type_hash = load_cell_type_hash(dex_index, Source::Input)?;
+        let data = load_cell_data(dex_index, Source::Input)?;
+        // The DEX cell of a bid holds the payment, which is a fungible token if it is paid in a UDT
+        let fungible = type_hash.is_some()
+            && if args.is_bid() {
+                args.unit_type_hash.is_some()
+            } else {
+                args.is_udt()
+            };
+        let sold_amount = if fungible {
+            let leftover_index = dex_index + 1;
+            let leftover_amount = if args.is_partial_fill()
+                && load_dex_output_total_value(args, leftover_index)?.is_some()
+            {
+                parse_udt_amount(&load_cell_data(leftover_index, Source::Output)?)?
+            } else {
+                0
+            };
+            Some(parse_udt_amount(&data)?.saturating_sub(leftover_amount))
+        } else {
+            None
+        };
+        Ok(TakerAsset {
+            type_hash,
+            data,
+            sold_amount,
+            capacity: load_cell_capacity(dex_index, Source::Input)?,
+        })
+    }
+
+    // Returns true if the output at the index, locked by the taker lock, carries the asset
+    fn is_received_by(&self, index: usize) -> Result<bool, Error> {
+        if load_cell_type_hash(index, Source::Output)? != self.type_hash {
+            return Ok(false);
+        }
+        let data = load_cell_data(index, Source::Output)?;
+        Ok(match self.sold_amount {
+            Some(amount) => parse_udt_amount(&data).is_ok_and(|received| received >= amount),
+            // A plain capacity cell is received in its capacity
+            None => {
+                data == self.data
+                    && (self.type_hash.is_some()
+                        || load_cell_capacity(index, Source::Output)? >= self.capacity)
+            }
+        })
+    }
+}
+
+// The taker proves itself by an input locked by the taker lock, or receives the whole asset of
+// the DEX input in an output locked by it. One output cannot deliver the assets of several orders,
+// so the outputs of the taker are assigned to the taken private orders of the same taker in the
+// order of their inputs, each order to the first free output which carries its asset.
+pub fn is_taker_allowed(taker_lock_hash: [u8; 32], dex_index: usize) -> Result<bool, Error> {
+    if QueryIter::new(load_cell_lock_hash, Source::Input).any(|hash| hash == taker_lock_hash) {
+        return Ok(true);
+    }
+    let taker_outputs: Vec<usize> = QueryIter::new(load_cell_lock_hash, Source::Output)
+        .enumerate()
+        .filter(|(_, lock_hash)| *lock_hash == taker_lock_hash)
+        .map(|(index, _)| index)
+        .collect();
+    let mut assigned = Vec::new();
+    for (index, args) in load_dex_inputs()? {
+        if args.taker_lock_hash() != Some(taker_lock_hash)
+            || (index != dex_index && !is_order_taken(&args, index)?)
+        {
+            continue;
+        }
+        let asset = TakerAsset::load(&args, index)?;
+        let mut received = None;
+        for &output in &taker_outputs {
+            if !assigned.contains(&output) && asset.is_received_by(output)? {
+                received = Some(output);
+                break;
+            }
+        }
+        if index == dex_index {
+            return Ok(received.is_some());
+        }
+        assigned.extend(received);
+    }
+    Ok(false)
+}
@@ -23,17 +23,22 @@ use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, prelude::*},
-    error::SysError,
     high_level::{load_cell_data, load_cell_lock_hash, load_script, QueryIter},
 };
 
+// The error codes are kept in the shared catalogue
+use dex_errors::SudtError as Error;
+
 // spec https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0025-simple-udt/0025-simple-udt.md#sudt-cell
 const UDT_AMOUNT_LEN: usize = 16;
 
 pub fn program_entry() -> i8 {
     ckb_std::debug!("This is a sample UDT contract!");
 
-    let script = load_script().unwrap();
+    let script = match load_script() {
+        Ok(script) => script,
+        Err(err) => return Error::from(err) as i8,
+    };
     let args: Bytes = script.args().unpack();
     ckb_std::debug!("script args is {:?}", args);
 
@@ -106,37 +111,3 @@ fn collect_outputs_amount() -> Result<u128, Error> {
         .collect::<Result<Vec<_>, Error>>()?;
     Ok(udt_list.into_iter().sum::<u128>())
 }
-
-/// Error code
-#[repr(i8)]
-pub enum Error {
-    IndexOutOfBound = 1,
-    ItemMissing,
-    LengthNotEnough,
-    WaitFailure,
-    InvalidFd,
-    OtherEndClosed,
-    MaxVmsSpawned,
-    MaxFdsCreated,
-    // write our custom error code below
-    AmountEncoding = 12,
-    InvalidAmount,
-}
-
-impl From<SysError> for Error {
-    fn from(err: SysError) -> Self {
-        use SysError::*;
-        match err {
-            IndexOutOfBound => Self::IndexOutOfBound,
-            ItemMissing => Self::ItemMissing,
-            LengthNotEnough(_) => Self::LengthNotEnough,
-            WaitFailure => Self::WaitFailure,
-            InvalidFd => Self::InvalidFd,
-            OtherEndClosed => Self::OtherEndClosed,
-            MaxVmsSpawned => Self::MaxVmsSpawned,
-            MaxFdsCreated => Self::MaxFdsCreated,
-            Encoding => Self::AmountEncoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
-        }
-    }
-}
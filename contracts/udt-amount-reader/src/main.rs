@@ -0,0 +1,62 @@
+#![no_std]
+#![cfg_attr(not(test), no_main)]
+
+#[cfg(test)]
+extern crate alloc;
+
+#[cfg(not(test))]
+use ckb_std::default_alloc;
+#[cfg(not(test))]
+ckb_std::entry!(program_entry);
+#[cfg(not(test))]
+default_alloc!();
+
+use ckb_std::{
+    ckb_constants::Source,
+    env::argv,
+    high_level::{inherited_fds, load_cell_data},
+    syscalls::{close, write},
+};
+
+// The error codes are kept in the shared catalogue
+use dex_errors::AmountReaderError as Error;
+use dex_types::{READER_SOURCE_INPUT, READER_SOURCE_OUTPUT, UDT_AMOUNT_LEN};
+
+/// program entry
+pub fn program_entry() -> i8 {
+    match main() {
+        Ok(_) => 0,
+        Err(err) => err as i8,
+    }
+}
+
+// Spawned by the dex-lock to read the amount of a UDT cell with the sUDT layout, where the data
+// starts with a little-endian u128 amount. The index and the source of the cell are in argv, and
+// the amount is written to the inherited fd.
+fn main() -> Result<(), Error> {
+    let argv = argv();
+    if argv.len() != 2 {
+        return Err(Error::ArgvInvalid);
+    }
+    let index: usize = argv[0]
+        .to_str()
+        .ok()
+        .and_then(|index| index.parse().ok())
+        .ok_or(Error::ArgvInvalid)?;
+    let source = match argv[1].to_str() {
+        Ok(READER_SOURCE_INPUT) => Source::Input,
+        Ok(READER_SOURCE_OUTPUT) => Source::Output,
+        _ => return Err(Error::ArgvInvalid),
+    };
+
+    let data = load_cell_data(index, source)?;
+    let amount = data.get(..UDT_AMOUNT_LEN).ok_or(Error::AmountEncoding)?;
+
+    let fd = *inherited_fds().first().ok_or(Error::InvalidFd)?;
+    let mut written = 0;
+    while written < amount.len() {
+        written += write(fd, &amount[written..])?;
+    }
+    close(fd)?;
+    Ok(())
+}
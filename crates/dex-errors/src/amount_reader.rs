@@ -0,0 +1,76 @@
+use core::fmt;
+
+/// The error codes of the `udt-amount-reader` contract, which are the exit codes of the spawned
+/// reader
+#[repr(i8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmountReaderError {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    WaitFailure,
+    InvalidFd,
+    OtherEndClosed,
+    MaxVmsSpawned,
+    MaxFdsCreated,
+    ArgvInvalid,
+    AmountEncoding = 10,
+    SysErrorUnknown,
+}
+
+impl AmountReaderError {
+    const ALL: [Self; 11] = [
+        Self::IndexOutOfBound,
+        Self::ItemMissing,
+        Self::LengthNotEnough,
+        Self::WaitFailure,
+        Self::InvalidFd,
+        Self::OtherEndClosed,
+        Self::MaxVmsSpawned,
+        Self::MaxFdsCreated,
+        Self::ArgvInvalid,
+        Self::AmountEncoding,
+        Self::SysErrorUnknown,
+    ];
+
+    pub fn code(self) -> i8 {
+        self as i8
+    }
+
+    /// The error with the code, None if the code is not in the catalogue
+    pub fn from_code(code: i8) -> Option<Self> {
+        Self::ALL.into_iter().find(|err| err.code() == code)
+    }
+
+    /// The reason of the error that can be shown to users
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::IndexOutOfBound => "the cell is out of bound",
+            Self::ItemMissing => "a cell field is missing",
+            Self::LengthNotEnough => "a cell field is longer than the buffer",
+            Self::WaitFailure => "failed to wait for a spawned script",
+            Self::InvalidFd => "the inherited file descriptor is invalid",
+            Self::OtherEndClosed => "the other end of the pipe is closed",
+            Self::MaxVmsSpawned => "too many scripts are spawned",
+            Self::MaxFdsCreated => "too many file descriptors are created",
+            Self::ArgvInvalid => "the index or the source of the cell in argv is invalid",
+            Self::AmountEncoding => "the UDT cell data is shorter than the amount",
+            Self::SysErrorUnknown => "a syscall returned an unknown error",
+        }
+    }
+}
+
+impl From<AmountReaderError> for i8 {
+    fn from(err: AmountReaderError) -> Self {
+        err.code()
+    }
+}
+
+impl fmt::Display for AmountReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.reason())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AmountReaderError {}
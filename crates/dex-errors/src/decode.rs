@@ -0,0 +1,158 @@
+//! Decoding of the script error of a failed transaction verification.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{AmountReaderError, DexLockError, SudtError};
+
+/// The cells whose script failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptSource {
+    Inputs,
+    Outputs,
+}
+
+/// The script of the cell which failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptField {
+    Lock,
+    Type,
+}
+
+/// The code hash of the failed script, as it appears in the path of the error page
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScriptCodeHash {
+    /// `by-data-hash`, the data hash of the code cell, for the hash types data, data1 and data2
+    Data([u8; 32]),
+    /// `by-type-hash`, the type script hash of the code cell
+    Type([u8; 32]),
+}
+
+/// The failure of a script in a transaction verification, as reported by the CKB node and
+/// ckb-testtool: `TransactionScriptError { source: Inputs[0].Lock, cause: ValidationFailure:
+/// see error code 7 on page https://.../by-type-hash/<code hash>.html#7 }`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScriptFailure {
+    pub source:    ScriptSource,
+    /// The index of the first cell of the script group
+    pub index:     usize,
+    pub field:     ScriptField,
+    pub code_hash: ScriptCodeHash,
+    pub code:      i8,
+}
+
+impl ScriptFailure {
+    /// Parse the script failure from the message of the verification error, None if the error
+    /// is not the validation failure of a script
+    pub fn parse(message: &str) -> Option<Self> {
+        let source = message.split("source: ").nth(1)?;
+        let (source, rest) = if let Some(rest) = source.strip_prefix("Inputs[") {
+            (ScriptSource::Inputs, rest)
+        } else {
+            (ScriptSource::Outputs, source.strip_prefix("Outputs[")?)
+        };
+        let (index, rest) = rest.split_once("].")?;
+        let index = index.parse().ok()?;
+        let field = if rest.starts_with("Lock") {
+            ScriptField::Lock
+        } else if rest.starts_with("Type") {
+            ScriptField::Type
+        } else {
+            return None;
+        };
+
+        let code_hash = if let Some(hash) = message.split("by-data-hash/").nth(1) {
+            ScriptCodeHash::Data(parse_hash(hash)?)
+        } else {
+            ScriptCodeHash::Type(parse_hash(message.split("by-type-hash/").nth(1)?)?)
+        };
+
+        let code = message.split("error code ").nth(1)?;
+        let end = code
+            .find(|c: char| c != '-' && !c.is_ascii_digit())
+            .unwrap_or(code.len());
+        let code = code[..end].parse().ok()?;
+
+        Some(Self {
+            source,
+            index,
+            field,
+            code_hash,
+            code,
+        })
+    }
+}
+
+// The code hash is printed as 64 hex digits
+fn parse_hash(hex: &str) -> Option<[u8; 32]> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex).get(..64)?;
+    let mut hash = [0u8; 32];
+    for (byte, digits) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// The SDL contracts, each of them with its own catalogue of error codes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Contract {
+    DexLock,
+    Sudt,
+    AmountReader,
+}
+
+/// The error of a failed script decoded in the catalogue of its contract
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContractError {
+    DexLock(DexLockError),
+    Sudt(SudtError),
+    AmountReader(AmountReaderError),
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DexLock(err) => err.fmt(f),
+            Self::Sudt(err) => err.fmt(f),
+            Self::AmountReader(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+/// The contracts known by their code hash. The codes of the contracts overlap, so a script
+/// failure is only decoded in the catalogue of the contract its script refers to.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorRegistry {
+    contracts: HashMap<ScriptCodeHash, Contract>,
+}
+
+impl ErrorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the code hash of a deployment of the contract
+    pub fn register(mut self, code_hash: ScriptCodeHash, contract: Contract) -> Self {
+        self.contracts.insert(code_hash, contract);
+        self
+    }
+
+    /// The contract of the failed script, None if its code hash is not registered
+    pub fn contract(&self, failure: &ScriptFailure) -> Option<Contract> {
+        self.contracts.get(&failure.code_hash).copied()
+    }
+
+    /// The error of the failed script, None if its contract is not registered or the code is not
+    /// in the catalogue of the contract
+    pub fn decode(&self, failure: &ScriptFailure) -> Option<ContractError> {
+        match self.contract(failure)? {
+            Contract::DexLock => DexLockError::from_code(failure.code).map(ContractError::DexLock),
+            Contract::Sudt => SudtError::from_code(failure.code).map(ContractError::Sudt),
+            Contract::AmountReader => {
+                AmountReaderError::from_code(failure.code).map(ContractError::AmountReader)
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use core::fmt;
+
+/// The error codes of the `dex-lock` contract
+#[repr(i8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DexLockError {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    LockArgsInvalid = 5,
+    DexOwnerLockNotMatch,
+    DexFTTotalValueNotMatch,
+    DexNFTTotalValueNotMatch,
+    DexSetupInvalid,
+    TotalValueOverflow = 10,
+    UnitTypeNotMatch,
+    TotalValueNotMatch,
+    DexReceiverLockNotMatch,
+    DexPartialFillInvalid,
+    DexPartialFillAmountInvalid,
+    DexPartialFillTotalValueNotMatch,
+    DexVersionUnknown,
+    UnitDataLengthNotEnough,
+    DexExpiryInvalid,
+    DexOrderExpired = 20,
+    DexCancelNotAuthorized,
+    DexNFTTypeNotMatch,
+    DexNFTPaymentInvalid,
+    DexRateInvalid,
+    DexShareLockNotMatch,
+    DexShareValueNotMatch,
+    DexOutputIndexConflict,
+    TakerNotAllowed,
+    DexAuctionInvalid,
+    DexAuctionBidInvalid = 30,
+    DexAuctionRefundNotMatch,
+    DexAuctionWinnerNotMatch,
+    DexBundleIncomplete,
+    DexRepriceInvalid,
+    DexAssetNotMatch,
+    SysErrorUnknown,
+    WaitFailure,
+    InvalidFd,
+    OtherEndClosed,
+    MaxVmsSpawned = 40,
+    MaxFdsCreated,
+    DexAmountReaderMissing,
+    DexAmountReaderFailed,
+}
+
+impl DexLockError {
+    const ALL: [Self; 43] = [
+        Self::IndexOutOfBound,
+        Self::ItemMissing,
+        Self::LengthNotEnough,
+        Self::Encoding,
+        Self::LockArgsInvalid,
+        Self::DexOwnerLockNotMatch,
+        Self::DexFTTotalValueNotMatch,
+        Self::DexNFTTotalValueNotMatch,
+        Self::DexSetupInvalid,
+        Self::TotalValueOverflow,
+        Self::UnitTypeNotMatch,
+        Self::TotalValueNotMatch,
+        Self::DexReceiverLockNotMatch,
+        Self::DexPartialFillInvalid,
+        Self::DexPartialFillAmountInvalid,
+        Self::DexPartialFillTotalValueNotMatch,
+        Self::DexVersionUnknown,
+        Self::UnitDataLengthNotEnough,
+        Self::DexExpiryInvalid,
+        Self::DexOrderExpired,
+        Self::DexCancelNotAuthorized,
+        Self::DexNFTTypeNotMatch,
+        Self::DexNFTPaymentInvalid,
+        Self::DexRateInvalid,
+        Self::DexShareLockNotMatch,
+        Self::DexShareValueNotMatch,
+        Self::DexOutputIndexConflict,
+        Self::TakerNotAllowed,
+        Self::DexAuctionInvalid,
+        Self::DexAuctionBidInvalid,
+        Self::DexAuctionRefundNotMatch,
+        Self::DexAuctionWinnerNotMatch,
+        Self::DexBundleIncomplete,
+        Self::DexRepriceInvalid,
+        Self::DexAssetNotMatch,
+        Self::SysErrorUnknown,
+        Self::WaitFailure,
+        Self::InvalidFd,
+        Self::OtherEndClosed,
+        Self::MaxVmsSpawned,
+        Self::MaxFdsCreated,
+        Self::DexAmountReaderMissing,
+        Self::DexAmountReaderFailed,
+    ];
+
+    pub fn code(self) -> i8 {
+        self as i8
+    }
+
+    /// The error with the code, None if the code is not in the catalogue
+    pub fn from_code(code: i8) -> Option<Self> {
+        Self::ALL.into_iter().find(|err| err.code() == code)
+    }
+
+    /// The reason of the error that can be shown to users
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::IndexOutOfBound => "a cell, a witness or a field is out of bound",
+            Self::ItemMissing => "a cell field is missing",
+            Self::LengthNotEnough => "a cell field is longer than the buffer",
+            Self::Encoding => "a cell field or the owner lock is not valid molecule",
+            Self::LockArgsInvalid => "the length of the lock args does not match the setup",
+            Self::DexOwnerLockNotMatch => "the payment is not sent to the owner lock",
+            Self::DexFTTotalValueNotMatch => {
+                "the payment of a fungible token order does not cover the price and the capacity"
+            }
+            Self::DexNFTTotalValueNotMatch => {
+                "the payment of an NFT order does not cover the price"
+            }
+            Self::DexSetupInvalid => "the lock args contain an invalid combination of flags",
+            Self::TotalValueOverflow => "the price overflows",
+            Self::UnitTypeNotMatch => "the payment is not in a unit accepted by the order",
+            Self::TotalValueNotMatch => "the UDT payment does not cover the price",
+            Self::DexReceiverLockNotMatch => "the payment is not sent to the receiver lock",
+            Self::DexPartialFillInvalid => "the leftover order of a partial fill is invalid",
+            Self::DexPartialFillAmountInvalid => "the amount sold by a partial fill is invalid",
+            Self::DexPartialFillTotalValueNotMatch => {
+                "the price of the leftover order of a partial fill is not the remaining price"
+            }
+            Self::DexVersionUnknown => "the version of the lock args is unknown",
+            Self::UnitDataLengthNotEnough => "the UDT cell data is shorter than the amount",
+            Self::DexExpiryInvalid => "the expiry is not an absolute block number or timestamp",
+            Self::DexOrderExpired => "the order is expired and can only be returned to the owner",
+            Self::DexCancelNotAuthorized => "the owner did not authorize the cancel or the reprice",
+            Self::DexNFTTypeNotMatch => "the NFT is not the one recorded in the order",
+            Self::DexNFTPaymentInvalid => "the CKB payment of an NFT carries a type or data",
+            Self::DexRateInvalid => "the rate of a share is invalid",
+            Self::DexShareLockNotMatch => "a share is not sent to its receiver",
+            Self::DexShareValueNotMatch => "a share is underpaid",
+            Self::DexOutputIndexConflict => "an output pays more than one order",
+            Self::TakerNotAllowed => "the taker is not allowed to fill the private order",
+            Self::DexAuctionInvalid => "the auction is invalid",
+            Self::DexAuctionBidInvalid => "the bid does not outbid the top bid",
+            Self::DexAuctionRefundNotMatch => "the previous bidder is not refunded",
+            Self::DexAuctionWinnerNotMatch => "the asset is not sent to the top bidder",
+            Self::DexBundleIncomplete => "not every member of the bundle is spent",
+            Self::DexRepriceInvalid => "the repriced order changes more than the price",
+            Self::DexAssetNotMatch => "the listed cell is not the asset recorded in the order",
+            Self::SysErrorUnknown => "a syscall returned an unknown error",
+            Self::WaitFailure => "failed to wait for a spawned script",
+            Self::InvalidFd => "the file descriptor is invalid",
+            Self::OtherEndClosed => "the other end of the pipe is closed",
+            Self::MaxVmsSpawned => "too many scripts are spawned",
+            Self::MaxFdsCreated => "too many file descriptors are created",
+            Self::DexAmountReaderMissing => "the amount reader is not in the cell deps",
+            Self::DexAmountReaderFailed => "the amount reader failed to read the amount",
+        }
+    }
+}
+
+impl From<DexLockError> for i8 {
+    fn from(err: DexLockError) -> Self {
+        err.code()
+    }
+}
+
+impl fmt::Display for DexLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.reason())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DexLockError {}
@@ -0,0 +1,23 @@
+//! The catalogue of the error codes returned by the SDL contracts.
+//!
+//! The codes are stable: a code is never reused or renumbered, and new errors are appended. The
+//! contracts return them, and off-chain services and the tests decode them with the same types.
+//! Enable the `std` feature to decode the error of a failed transaction verification, and
+//! `ckb-std` to convert the syscall errors of ckb-std.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod amount_reader;
+#[cfg(feature = "std")]
+mod decode;
+mod dex_lock;
+mod sudt;
+mod sys;
+
+pub use amount_reader::AmountReaderError;
+#[cfg(feature = "std")]
+pub use decode::{
+    Contract, ContractError, ErrorRegistry, ScriptCodeHash, ScriptFailure, ScriptField,
+    ScriptSource,
+};
+pub use dex_lock::DexLockError;
+pub use sudt::SudtError;
@@ -0,0 +1,75 @@
+use core::fmt;
+
+/// The error codes of the `sudt` contract
+#[repr(i8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SudtError {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    WaitFailure,
+    InvalidFd,
+    OtherEndClosed,
+    MaxVmsSpawned,
+    MaxFdsCreated,
+    AmountEncoding = 12,
+    InvalidAmount,
+    SysErrorUnknown,
+}
+
+impl SudtError {
+    const ALL: [Self; 11] = [
+        Self::IndexOutOfBound,
+        Self::ItemMissing,
+        Self::LengthNotEnough,
+        Self::WaitFailure,
+        Self::InvalidFd,
+        Self::OtherEndClosed,
+        Self::MaxVmsSpawned,
+        Self::MaxFdsCreated,
+        Self::AmountEncoding,
+        Self::InvalidAmount,
+        Self::SysErrorUnknown,
+    ];
+
+    pub fn code(self) -> i8 {
+        self as i8
+    }
+
+    /// The error with the code, None if the code is not in the catalogue
+    pub fn from_code(code: i8) -> Option<Self> {
+        Self::ALL.into_iter().find(|err| err.code() == code)
+    }
+
+    /// The reason of the error that can be shown to users
+    pub fn reason(self) -> &'static str {
+        match self {
+            Self::IndexOutOfBound => "a cell or a field is out of bound",
+            Self::ItemMissing => "a cell field is missing",
+            Self::LengthNotEnough => "a cell field is longer than the buffer",
+            Self::WaitFailure => "failed to wait for a spawned script",
+            Self::InvalidFd => "the file descriptor is invalid",
+            Self::OtherEndClosed => "the other end of the pipe is closed",
+            Self::MaxVmsSpawned => "too many scripts are spawned",
+            Self::MaxFdsCreated => "too many file descriptors are created",
+            Self::AmountEncoding => "the UDT cell data is shorter than the amount",
+            Self::InvalidAmount => "the outputs hold more UDT than the inputs",
+            Self::SysErrorUnknown => "a syscall returned an unknown error",
+        }
+    }
+}
+
+impl From<SudtError> for i8 {
+    fn from(err: SudtError) -> Self {
+        err.code()
+    }
+}
+
+impl fmt::Display for SudtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.reason())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SudtError {}
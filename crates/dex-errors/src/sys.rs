@@ -0,0 +1,62 @@
+//! Conversions of the syscall errors of the ckb-std version used by the contracts. A syscall
+//! error unknown to the contract is returned as its own code instead of aborting the script.
+
+#[cfg(feature = "ckb-std")]
+impl From<ckb_std::error::SysError> for crate::DexLockError {
+    fn from(err: ckb_std::error::SysError) -> Self {
+        use ckb_std::error::SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            WaitFailure => Self::WaitFailure,
+            InvalidFd => Self::InvalidFd,
+            OtherEndClosed => Self::OtherEndClosed,
+            MaxVmsSpawned => Self::MaxVmsSpawned,
+            MaxFdsCreated => Self::MaxFdsCreated,
+            #[allow(unreachable_patterns)]
+            _ => Self::SysErrorUnknown,
+        }
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<ckb_std::error::SysError> for crate::SudtError {
+    fn from(err: ckb_std::error::SysError) -> Self {
+        use ckb_std::error::SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            WaitFailure => Self::WaitFailure,
+            InvalidFd => Self::InvalidFd,
+            OtherEndClosed => Self::OtherEndClosed,
+            MaxVmsSpawned => Self::MaxVmsSpawned,
+            MaxFdsCreated => Self::MaxFdsCreated,
+            Encoding => Self::AmountEncoding,
+            #[allow(unreachable_patterns)]
+            _ => Self::SysErrorUnknown,
+        }
+    }
+}
+
+#[cfg(feature = "ckb-std")]
+impl From<ckb_std::error::SysError> for crate::AmountReaderError {
+    fn from(err: ckb_std::error::SysError) -> Self {
+        use ckb_std::error::SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            WaitFailure => Self::WaitFailure,
+            InvalidFd => Self::InvalidFd,
+            OtherEndClosed => Self::OtherEndClosed,
+            MaxVmsSpawned => Self::MaxVmsSpawned,
+            MaxFdsCreated => Self::MaxFdsCreated,
+            Encoding => Self::AmountEncoding,
+            #[allow(unreachable_patterns)]
+            _ => Self::SysErrorUnknown,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use dex_types::ArgsError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The args of the order are invalid
+    Args(ArgsError),
+    /// The cell is not locked by the DEX lock of the config
+    NotDexCell,
+    /// The order has a receiver_lock but its script is not given or does not match the hash
+    ReceiverLockNotMatch,
+    /// The order has a share but the lock script of its receiver is not given
+    ShareLockNotFound,
+    /// The order is private and the buyer lock is not its taker lock
+    TakerNotAllowed,
+    /// The payment unit is not one of the accepted units of the order
+    UnitNotAccepted,
+    /// The order is a bid or an English auction, which is not taken by a payment
+    OrderNotSupported,
+    /// The unit of the order is counted in cells or read by an amount reader, which cannot be
+    /// split from the buyer cells
+    AmountFormatNotSupported,
+    /// The buyer cells are not enough to fill the inputs between the orders
+    NotEnoughCells,
+    /// The capacity of the given cells is not enough to pay the orders and the fee
+    InsufficientCapacity,
+    /// The UDT amount of the given cells is not enough to pay the orders
+    InsufficientUdt,
+    /// The cell data of a UDT cell is shorter than the amount
+    UdtAmountInvalid,
+    /// A value does not fit in the capacity or the UDT amount
+    Overflow,
+}
+
+impl From<ArgsError> for BuildError {
+    fn from(err: ArgsError) -> Self {
+        BuildError::Args(err)
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Args(err) => write!(f, "invalid order args: {}", err),
+            Self::NotDexCell => f.write_str("the cell is not locked by the DEX lock"),
+            Self::ReceiverLockNotMatch => {
+                f.write_str("the receiver lock script is missing or does not match")
+            }
+            Self::ShareLockNotFound => {
+                f.write_str("the lock script of a share receiver is missing")
+            }
+            Self::TakerNotAllowed => f.write_str("the buyer lock is not the taker of the order"),
+            Self::UnitNotAccepted => f.write_str("the order does not accept the payment unit"),
+            Self::OrderNotSupported => {
+                f.write_str("bids and English auctions cannot be bought with a payment")
+            }
+            Self::AmountFormatNotSupported => f.write_str(
+                "the payment in counted cells or read by a reader must be built by the caller",
+            ),
+            Self::NotEnoughCells => {
+                f.write_str("not enough buyer cells to fill the inputs between the orders")
+            }
+            Self::InsufficientCapacity => f.write_str("insufficient capacity"),
+            Self::InsufficientUdt => f.write_str("insufficient UDT amount"),
+            Self::UdtAmountInvalid => f.write_str("the UDT cell data is shorter than 16 bytes"),
+            Self::Overflow => f.write_str("value overflow"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
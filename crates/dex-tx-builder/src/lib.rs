@@ -0,0 +1,13 @@
+//! Builders of the unsigned transactions in the SDL transaction templates: listing, matching,
+//! canceling and repricing orders settled in CKB or in a UDT.
+//!
+//! The builders only place the cells, the caller is responsible for the cell deps of the locks
+//! and types of its own cells, the witnesses and the signatures.
+
+mod error;
+mod templates;
+mod types;
+
+pub use error::BuildError;
+pub use templates::{buy_orders, cancel_orders, list_order, reprice_order};
+pub use types::{DexLockConfig, LiveCell, Order};
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionBuilder, TransactionView},
+    packed::{CellInput, CellOutput, Script, ScriptOpt, WitnessArgs},
+    prelude::*,
+};
+use dex_types::{witness, AmountFormat, DexArgs};
+
+use crate::{BuildError, DexLockConfig, LiveCell, Order};
+
+/// Builds the "Listing" template: the asset cell is re-locked by the DEX lock with the args at
+/// output 0, and the rest of the capacity goes back to the change lock.
+pub fn list_order(
+    config: &DexLockConfig,
+    asset: &LiveCell,
+    args: &DexArgs,
+    capacity_cells: &[LiveCell],
+    change_lock: &Script,
+    fee: u64,
+) -> Result<TransactionView, BuildError> {
+    let dex_lock = config.lock_script(args)?;
+    // The DEX lock args may be larger than the original lock, so the listed cell may need more
+    // capacity than before
+    let dex_cell = build_output(
+        asset.capacity(),
+        dex_lock,
+        asset.output.type_(),
+        &asset.data,
+    )?;
+
+    let inputs: Vec<&LiveCell> = [asset].into_iter().chain(capacity_cells).collect();
+    let mut outputs = vec![(dex_cell, asset.data.clone())];
+    push_change(&inputs, &mut outputs, change_lock, fee)?;
+    Ok(build_tx(config, &inputs, outputs))
+}
+
+/// Builds the "Matching" template: the payment of each order is the output at the index of its
+/// input followed by the outputs of its shares, and the buyer cells fill the inputs in between.
+/// A bundle is paid once at the index of its first member, so all of its members must be given,
+/// and the outputs at the indexes of the other members take the assets sent to the buyer lock.
+/// The rest of the assets and the UDT and CKB change of the buyer come last. Bids and English
+/// auctions are not taken by a payment and are rejected.
+pub fn buy_orders(
+    config: &DexLockConfig,
+    orders: &[Order],
+    buyer_cells: &[LiveCell],
+    buyer_lock: &Script,
+    fee: u64,
+) -> Result<TransactionView, BuildError> {
+    // The amount format of the unit of each order, the other UDTs have the sUDT layout. Counted
+    // cells cannot be split and the layout read by an amount reader is unknown, so they are not
+    // taken from the balances below.
+    let mut unit_formats: BTreeMap<[u8; 32], AmountFormat> = BTreeMap::new();
+    for order in orders {
+        let args = order.args(config)?;
+        if args.is_bid() || args.english_auction().is_some() {
+            return Err(BuildError::OrderNotSupported);
+        }
+        if let Some(unit_type_hash) = args.unit_type_hash {
+            if args.amount_format() == AmountFormat::CellCount || args.amount_reader().is_some() {
+                return Err(BuildError::AmountFormatNotSupported);
+            }
+            unit_formats.insert(unit_type_hash, args.amount_format());
+        }
+    }
+    // The type scripts of the buyer's UDT cells, the amount of each of them and its format
+    let mut udt_balances: BTreeMap<[u8; 32], (Script, u128, AmountFormat)> = BTreeMap::new();
+    for cell in buyer_cells {
+        if let Some(type_) = cell.output.type_().to_opt() {
+            let type_hash: [u8; 32] = type_.calc_script_hash().unpack();
+            let format = unit_formats.get(&type_hash).copied().unwrap_or_default();
+            let amount = format
+                .amount(&cell.data)
+                .ok_or(BuildError::UdtAmountInvalid)?;
+            let balance = udt_balances.entry(type_hash).or_insert((type_, 0, format));
+            balance.1 = balance.1.checked_add(amount).ok_or(BuildError::Overflow)?;
+        }
+    }
+
+    // The asset of each order sent to the buyer lock, in the order of the orders
+    let mut buyer_assets = orders
+        .iter()
+        .map(|order| {
+            let asset = &order.cell;
+            let output = build_output(0, buyer_lock.clone(), asset.output.type_(), &asset.data)?;
+            Ok((output, asset.data.clone()))
+        })
+        .collect::<Result<Vec<_>, BuildError>>()?
+        .into_iter();
+
+    let mut inputs: Vec<&LiveCell> = Vec::with_capacity(orders.len() + buyer_cells.len());
+    let mut outputs = Vec::with_capacity(orders.len() * 2 + 2);
+    let mut spare_cells = buyer_cells.iter();
+    let mut order_sinces = Vec::with_capacity(orders.len());
+    for (position, order) in orders.iter().enumerate() {
+        let args = order.args(config)?;
+        let dex_lock = order.cell.output.lock();
+        let is_bundle_member =
+            |other: &Order| args.bundle().is_some() && other.cell.output.lock() == dex_lock;
+        // The other members of a bundle are paid by the output of the first one
+        if orders[..position].iter().any(is_bundle_member) {
+            inputs.push(&order.cell);
+            continue;
+        }
+        // The outputs at the indexes of the other members of a bundle are not constrained, so the
+        // assets of the orders before fill them and the order keeps the index of its payment
+        while outputs.len() < inputs.len() {
+            outputs.push(buyer_assets.next().ok_or(BuildError::NotEnoughCells)?);
+        }
+        while inputs.len() < outputs.len() {
+            inputs.push(spare_cells.next().ok_or(BuildError::NotEnoughCells)?);
+        }
+        inputs.push(&order.cell);
+
+        // The asset of a private order must be sent to its taker
+        let buyer_lock_hash: [u8; 32] = buyer_lock.calc_script_hash().unpack();
+        if args
+            .taker_lock_hash()
+            .is_some_and(|hash| hash != buyer_lock_hash)
+        {
+            return Err(BuildError::TakerNotAllowed);
+        }
+        let unit = order.unit(&args)?;
+        let price = unit.total_value;
+        order_sinces.push((inputs.len() - 1, order.since));
+        let mut seller_value = price;
+        let mut shares = Vec::new();
+        for share in [args.royalty(), args.platform_fee()].into_iter().flatten() {
+            shares.push((share, share.amount(price)));
+        }
+        for (_, value) in &shares {
+            seller_value -= value;
+        }
+
+        // A fungible token order sold for CKB also pays back the capacity of the DEX cell, or of
+        // every member of a bundle
+        let extra_capacity = if unit.unit_type_hash.is_some() || args.is_nft() {
+            0
+        } else if args.bundle().is_some() {
+            sum_capacity(
+                orders
+                    .iter()
+                    .filter(|other| is_bundle_member(other))
+                    .map(|member| member.cell.capacity()),
+            )?
+        } else {
+            order.cell.capacity()
+        };
+        let payment_lock = order.payment_lock(&args)?;
+        outputs.push(build_payment(
+            unit.unit_type_hash,
+            &mut udt_balances,
+            payment_lock,
+            seller_value,
+            extra_capacity,
+        )?);
+        // A share rounded down to zero has no output
+        for (share, value) in shares.into_iter().filter(|(_, value)| *value > 0) {
+            let share_lock = order.find_share_lock(&share.lock_hash)?;
+            outputs.push(build_payment(
+                unit.unit_type_hash,
+                &mut udt_balances,
+                share_lock,
+                value,
+                0,
+            )?);
+        }
+    }
+    inputs.extend(spare_cells);
+
+    outputs.extend(buyer_assets);
+    for (unit_type, balance, format) in udt_balances.into_values() {
+        let data = udt_data(format, balance);
+        let output = build_output(0, buyer_lock.clone(), Some(unit_type).pack(), &data)?;
+        outputs.push((output, data));
+    }
+
+    push_change(&inputs, &mut outputs, buyer_lock, fee)?;
+    let mut tx_inputs: Vec<CellInput> = inputs.iter().map(|cell| cell.input()).collect();
+    for (index, since) in order_sinces {
+        tx_inputs[index] = tx_inputs[index]
+            .clone()
+            .as_builder()
+            .since(since.pack())
+            .build();
+    }
+    Ok(build_tx(config, &inputs, outputs)
+        .as_advanced_builder()
+        .set_inputs(tx_inputs)
+        .build())
+}
+
+// Builds an output paying the value in the unit, the UDT is taken from the balances of the buyer
+fn build_payment(
+    unit_type_hash: Option<[u8; 32]>,
+    udt_balances: &mut BTreeMap<[u8; 32], (Script, u128, AmountFormat)>,
+    lock: Script,
+    value: u128,
+    extra_capacity: u64,
+) -> Result<(CellOutput, Bytes), BuildError> {
+    match unit_type_hash {
+        Some(unit_type_hash) => {
+            let (unit_type, balance, format) = udt_balances
+                .get_mut(&unit_type_hash)
+                .ok_or(BuildError::InsufficientUdt)?;
+            *balance = balance
+                .checked_sub(value)
+                .ok_or(BuildError::InsufficientUdt)?;
+            let data = udt_data(*format, value);
+            let output = build_output(0, lock, Some(unit_type.clone()).pack(), &data)?;
+            Ok((output, data))
+        }
+        None => {
+            let capacity = value
+                .checked_add(extra_capacity as u128)
+                .ok_or(BuildError::Overflow)?;
+            let capacity = u64::try_from(capacity).map_err(|_| BuildError::Overflow)?;
+            let output = build_output(capacity, lock, ScriptOpt::default(), &[])?;
+            Ok((output, Bytes::new()))
+        }
+    }
+}
+
+/// Builds the "Canceling an Order" template: the owner cells come first to prove the owner, and
+/// the assets of the orders and the change are sent to the given lock. The witnesses of the orders
+/// with a scoped cancel are marked with the owner lock hash.
+pub fn cancel_orders(
+    config: &DexLockConfig,
+    orders: &[LiveCell],
+    owner_cells: &[LiveCell],
+    to_lock: &Script,
+    fee: u64,
+) -> Result<TransactionView, BuildError> {
+    let mut outputs = Vec::with_capacity(orders.len() + 1);
+    let mut witnesses: Vec<Bytes> = owner_cells.iter().map(|_| Bytes::new()).collect();
+    for order in orders {
+        let args = Order::new(order.clone()).args(config)?;
+        witnesses.push(if args.is_scoped_cancel() {
+            let owner_lock = Script::from_slice(&args.owner_lock)
+                .map_err(|_| BuildError::Args(dex_types::ArgsError::Encoding))?;
+            let mark = witness::cancel_mark(owner_lock.calc_script_hash().unpack());
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::from(mark.to_vec())).pack())
+                .build()
+                .as_bytes()
+        } else {
+            Bytes::new()
+        });
+        let output = build_output(
+            order.capacity(),
+            to_lock.clone(),
+            order.output.type_(),
+            &order.data,
+        )?;
+        outputs.push((output, order.data.clone()));
+    }
+
+    let inputs: Vec<&LiveCell> = owner_cells.iter().chain(orders).collect();
+    push_change(&inputs, &mut outputs, to_lock, fee)?;
+    Ok(build_tx(config, &inputs, outputs)
+        .as_advanced_builder()
+        .set_witnesses(
+            witnesses
+                .into_iter()
+                .map(|witness| witness.pack())
+                .collect(),
+        )
+        .build())
+}
+
+/// Builds the "Repricing an Order" template: the order is re-locked at output 0 with only a new
+/// total_value, and the owner cells after it prove the owner. The witness of the order carries
+/// the reprice mark and the change goes back to the owner lock.
+pub fn reprice_order(
+    config: &DexLockConfig,
+    order: &LiveCell,
+    total_value: u128,
+    owner_cells: &[LiveCell],
+    fee: u64,
+) -> Result<TransactionView, BuildError> {
+    let args = Order::new(order.clone()).args(config)?;
+    let owner_lock = Script::from_slice(&args.owner_lock)
+        .map_err(|_| BuildError::Args(dex_types::ArgsError::Encoding))?;
+    let repriced_args = DexArgs {
+        total_value,
+        ..args
+    };
+    let dex_cell = order
+        .output
+        .clone()
+        .as_builder()
+        .lock(config.lock_script(&repriced_args)?)
+        .build();
+
+    let inputs: Vec<&LiveCell> = [order].into_iter().chain(owner_cells).collect();
+    let mut outputs = vec![(dex_cell, order.data.clone())];
+    push_change(&inputs, &mut outputs, &owner_lock, fee)?;
+    let mark = witness::reprice_mark(owner_lock.calc_script_hash().unpack());
+    let mut witnesses = vec![WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(mark.to_vec())).pack())
+        .build()
+        .as_bytes()
+        .pack()];
+    witnesses.extend(owner_cells.iter().map(|_| Bytes::new().pack()));
+    Ok(build_tx(config, &inputs, outputs)
+        .as_advanced_builder()
+        .set_witnesses(witnesses)
+        .build())
+}
+
+// Builds a cell output with at least the given capacity and at least its occupied capacity
+fn build_output(
+    capacity: u64,
+    lock: Script,
+    type_: ScriptOpt,
+    data: &[u8],
+) -> Result<CellOutput, BuildError> {
+    let output = CellOutput::new_builder().lock(lock).type_(type_).build();
+    let data_capacity = Capacity::bytes(data.len()).map_err(|_| BuildError::Overflow)?;
+    let occupied = output
+        .occupied_capacity(data_capacity)
+        .map_err(|_| BuildError::Overflow)?
+        .as_u64();
+    Ok(output
+        .as_builder()
+        .capacity(capacity.max(occupied).pack())
+        .build())
+}
+
+// Sends the capacity left after the outputs and the fee to the change lock
+fn push_change(
+    inputs: &[&LiveCell],
+    outputs: &mut Vec<(CellOutput, Bytes)>,
+    change_lock: &Script,
+    fee: u64,
+) -> Result<(), BuildError> {
+    let inputs_capacity = sum_capacity(inputs.iter().map(|cell| cell.capacity()))?;
+    let outputs_capacity =
+        sum_capacity(outputs.iter().map(|(output, _)| output.capacity().unpack()))?;
+    let change = inputs_capacity
+        .checked_sub(outputs_capacity)
+        .and_then(|change| change.checked_sub(fee))
+        .ok_or(BuildError::InsufficientCapacity)?;
+    let change_output = build_output(0, change_lock.clone(), ScriptOpt::default(), &[])?;
+    if change < change_output.capacity().unpack() {
+        return Err(BuildError::InsufficientCapacity);
+    }
+    outputs.push((
+        change_output.as_builder().capacity(change.pack()).build(),
+        Bytes::new(),
+    ));
+    Ok(())
+}
+
+fn sum_capacity(mut capacities: impl Iterator<Item = u64>) -> Result<u64, BuildError> {
+    capacities.try_fold(0u64, |sum, capacity| {
+        sum.checked_add(capacity).ok_or(BuildError::Overflow)
+    })
+}
+
+// The cell data holding the UDT amount in the format
+fn udt_data(format: AmountFormat, amount: u128) -> Bytes {
+    match format {
+        AmountFormat::UdtBigEndian => Bytes::from(amount.to_be_bytes().to_vec()),
+        _ => Bytes::from(amount.to_le_bytes().to_vec()),
+    }
+}
+
+fn build_tx(
+    config: &DexLockConfig,
+    inputs: &[&LiveCell],
+    outputs: Vec<(CellOutput, Bytes)>,
+) -> TransactionView {
+    let (outputs, outputs_data): (Vec<_>, Vec<_>) = outputs.into_iter().unzip();
+    TransactionBuilder::default()
+        .inputs(inputs.iter().map(|cell| cell.input()))
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(config.cell_dep.clone())
+        .witnesses(inputs.iter().map(|_| Bytes::new().pack()))
+        .build()
+}
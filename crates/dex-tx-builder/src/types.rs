@@ -0,0 +1,167 @@
+use ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+use dex_types::{AcceptedUnit, AssetCommitment, DexArgs};
+
+use crate::BuildError;
+
+/// A live cell with its out point, the builders spend it as an input
+#[derive(Debug, Clone)]
+pub struct LiveCell {
+    pub out_point: OutPoint,
+    pub output:    CellOutput,
+    pub data:      Bytes,
+}
+
+impl LiveCell {
+    pub fn capacity(&self) -> u64 {
+        self.output.capacity().unpack()
+    }
+
+    pub fn input(&self) -> CellInput {
+        CellInput::new_builder()
+            .previous_output(self.out_point.clone())
+            .build()
+    }
+
+    pub fn type_hash(&self) -> Option<Byte32> {
+        self.output
+            .type_()
+            .to_opt()
+            .map(|type_| type_.calc_script_hash())
+    }
+
+    /// The commitment to the cell as the listed asset of an order
+    pub fn asset_commitment(&self) -> AssetCommitment {
+        AssetCommitment {
+            type_hash: self.type_hash().map(|hash| hash.unpack()),
+            data_hash: CellOutput::calc_data_hash(&self.data).unpack(),
+        }
+    }
+}
+
+/// The deployment of the DEX lock
+#[derive(Debug, Clone)]
+pub struct DexLockConfig {
+    pub code_hash: Byte32,
+    pub hash_type: ScriptHashType,
+    pub cell_dep:  CellDep,
+}
+
+impl DexLockConfig {
+    pub fn lock_script(&self, args: &DexArgs) -> Result<Script, BuildError> {
+        Ok(Script::new_builder()
+            .code_hash(self.code_hash.clone())
+            .hash_type(self.hash_type.into())
+            .args(Bytes::from(args.to_vec()?).pack())
+            .build())
+    }
+
+    pub fn is_dex_lock(&self, lock: &Script) -> bool {
+        lock.code_hash() == self.code_hash && lock.hash_type() == self.hash_type.into()
+    }
+}
+
+/// A listed order to buy, the receiver lock script is required if the order has a receiver_lock
+/// and the lock scripts of the share receivers if the order has shares, because the args only
+/// contain their hashes. The since of the order input proves the price of a Dutch auction, and
+/// the payment unit is one of the accepted units of the order, its own unit by default.
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub cell:          LiveCell,
+    pub receiver_lock: Option<Script>,
+    pub share_locks:   Vec<Script>,
+    pub since:         u64,
+    pub payment_unit:  Option<AcceptedUnit>,
+}
+
+impl Order {
+    pub fn new(cell: LiveCell) -> Self {
+        Order {
+            cell,
+            receiver_lock: None,
+            share_locks: Vec::new(),
+            since: 0,
+            payment_unit: None,
+        }
+    }
+
+    pub fn with_receiver_lock(cell: LiveCell, receiver_lock: Script) -> Self {
+        Order {
+            cell,
+            receiver_lock: Some(receiver_lock),
+            share_locks: Vec::new(),
+            since: 0,
+            payment_unit: None,
+        }
+    }
+
+    /// Adds the lock script of a share receiver, such as the royalty or the platform fee receiver
+    pub fn share_lock(mut self, lock: Script) -> Self {
+        self.share_locks.push(lock);
+        self
+    }
+
+    /// Sets the since of the order input, an absolute block number or timestamp that the
+    /// transaction can only be committed after
+    pub fn since(mut self, since: u64) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Pays the order in another of its accepted units
+    pub fn pay_in(mut self, unit: AcceptedUnit) -> Self {
+        self.payment_unit = Some(unit);
+        self
+    }
+
+    /// The unit the order is paid in and the price in it
+    pub fn unit(&self, args: &DexArgs) -> Result<AcceptedUnit, BuildError> {
+        let unit = match self.payment_unit {
+            Some(unit) if args.accepted_units().contains(&unit) => unit,
+            Some(_) => return Err(BuildError::UnitNotAccepted),
+            None => args.accepted_units()[0],
+        };
+        // The price of a Dutch auction is the one proven by the since of the order input
+        if unit.unit_type_hash == args.unit_type_hash {
+            return Ok(AcceptedUnit {
+                total_value: args.price(self.since),
+                ..unit
+            });
+        }
+        Ok(unit)
+    }
+
+    pub fn args(&self, config: &DexLockConfig) -> Result<DexArgs, BuildError> {
+        let lock = self.cell.output.lock();
+        if !config.is_dex_lock(&lock) {
+            return Err(BuildError::NotDexCell);
+        }
+        let data: Bytes = lock.args().unpack();
+        Ok(DexArgs::from_slice(&data)?)
+    }
+
+    /// The lock script of the share receiver with the hash
+    pub fn find_share_lock(&self, lock_hash: &[u8; 32]) -> Result<Script, BuildError> {
+        self.share_locks
+            .iter()
+            .find(|lock| lock.calc_script_hash().as_slice() == lock_hash)
+            .cloned()
+            .ok_or(BuildError::ShareLockNotFound)
+    }
+
+    /// The lock script the payment must be sent to
+    pub fn payment_lock(&self, args: &DexArgs) -> Result<Script, BuildError> {
+        match (args.receiver_lock, &self.receiver_lock) {
+            (Some(hash), Some(lock)) if lock.calc_script_hash().as_slice() == hash => {
+                Ok(lock.clone())
+            }
+            (Some(_), _) => Err(BuildError::ReceiverLockNotMatch),
+            (None, _) => Script::from_slice(&args.owner_lock)
+                .map_err(|_| BuildError::Args(dex_types::ArgsError::Encoding)),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::ArgsError;
+
+/// The size of a serialized amount format
+pub const AMOUNT_FORMAT_SIZE: usize = 1;
+
+/// The size of a `u128` amount at the start of the cell data
+pub const UDT_AMOUNT_LEN: usize = 16;
+
+/// How the amount of a cell of the unit type is read from the cell
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmountFormat {
+    /// The data starts with a little-endian `u128` amount, the layout of sUDT and of xUDT which
+    /// may carry extension data after the amount
+    #[default]
+    Udt = 0,
+    /// The data starts with a big-endian `u128` amount
+    UdtBigEndian = 1,
+    /// Every cell of the type counts as one, whatever its data, such as the items of a
+    /// collection. A payment is the run of outputs with the same lock and type starting at its
+    /// index.
+    CellCount = 2,
+}
+
+impl AmountFormat {
+    pub fn from_bytes(data: [u8; AMOUNT_FORMAT_SIZE]) -> Result<Self, ArgsError> {
+        match data[0] {
+            0 => Ok(Self::Udt),
+            1 => Ok(Self::UdtBigEndian),
+            2 => Ok(Self::CellCount),
+            _ => Err(ArgsError::DexSetupInvalid),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; AMOUNT_FORMAT_SIZE] {
+        [*self as u8]
+    }
+
+    /// The amount held by one cell with the data, None if the data is shorter than the amount
+    pub fn amount(&self, data: &[u8]) -> Option<u128> {
+        let mut amount = [0u8; UDT_AMOUNT_LEN];
+        match self {
+            Self::Udt => {
+                amount.copy_from_slice(data.get(..UDT_AMOUNT_LEN)?);
+                Some(u128::from_le_bytes(amount))
+            }
+            Self::UdtBigEndian => {
+                amount.copy_from_slice(data.get(..UDT_AMOUNT_LEN)?);
+                Some(u128::from_be_bytes(amount))
+            }
+            Self::CellCount => Some(1),
+        }
+    }
+}
@@ -0,0 +1,943 @@
+use alloc::vec::Vec;
+
+use crate::{
+    extension::{
+        self, AGGREGATE_PAYMENT, ALT_UNITS, AMOUNT_FORMAT, AMOUNT_READER, ASSET_COMMITMENT, BID,
+        BUNDLE, DUTCH_AUCTION, ENGLISH_AUCTION, EXPIRY, FLAGS_SIZE, NFT_TYPE_HASH, PLATFORM_FEE,
+        ROYALTY, SCOPED_CANCEL, TAKER_LOCK_HASH,
+    },
+    script::verify_script,
+    setup::{
+        self, ARGS_VERSION_0, ARGS_VERSION_1, NFT, PARTIAL_FILL, RECEIVER_LOCK, UNIT_TYPE_HASH,
+    },
+    since, AcceptedUnit, AmountFormat, AmountReader, ArgsError, AssetCommitment, Bundle,
+    DutchAuction, EnglishAuction, FeeShare, ACCEPTED_UNIT_SIZE, AMOUNT_FORMAT_SIZE,
+    AMOUNT_READER_SIZE, ASSET_COMMITMENT_SIZE, AUCTION_SIZE, BASIS_POINTS, BUNDLE_SIZE,
+    ENGLISH_AUCTION_SIZE, MAX_ALT_UNITS, SHARE_SIZE,
+};
+
+/// The minimum length of the lock args, which is the minimum length of a serialized lock script
+/// (49 bytes) plus the setup byte (1 byte) and the total value (16 bytes)
+pub const MIN_ARGS_SIZE: usize = 66;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DexArgs {
+    // the molecule serialized owner lock script
+    pub owner_lock:     Vec<u8>,
+    // 0b_xxxx_x0xx is for FT asset and 0b_xxxx_x1xx is for NFT asset
+    // 0b_xxxx_1xxx allows the FT asset to be partially filled
+    pub setup:          u8,
+    pub total_value:    u128,
+    // Optional, the lock script hash of the payment receiver
+    pub receiver_lock:  Option<[u8; 32]>,
+    pub unit_type_hash: Option<[u8; 32]>,
+    // Only exists in the version 1 args
+    pub extension:      Option<DexExtension>,
+}
+
+/// The optional fields of the version 1 args after the fields of version 0
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DexExtension {
+    pub flags:           u16,
+    // Optional, the absolute block number or timestamp in the since format after which the order
+    // expires
+    pub expiry:          Option<u64>,
+    // Optional, the type script hash of the listed NFT
+    pub nft_type_hash:   Option<[u8; 32]>,
+    // Optional, the creator royalty paid in the same unit as the order
+    pub royalty:         Option<FeeShare>,
+    // Optional, the marketplace fee paid in the same unit as the order after the royalty
+    pub platform_fee:    Option<FeeShare>,
+    // Optional, the lock script hash of the only taker allowed to fill the order
+    pub taker_lock_hash: Option<[u8; 32]>,
+    // Optional, the type script hash of the asset wanted by a bid
+    pub bid_type_hash:   Option<[u8; 32]>,
+    // Optional, the price decays from the total_value to the end value of the auction
+    pub dutch_auction:   Option<DutchAuction>,
+    // Optional, the deadline and the top bidder of an English auction
+    pub english_auction: Option<EnglishAuction>,
+    // Optional, the id and the number of the cells sold together
+    pub bundle:          Option<Bundle>,
+    // Optional, the other units the order can be paid in and the price in each of them
+    pub alt_units:       Vec<AcceptedUnit>,
+    // Optional, the type hash and the data hash of the listed asset
+    pub asset:           Option<AssetCommitment>,
+    // Optional, the script spawned to read the amount of a payment in the unit
+    pub amount_reader:   Option<AmountReader>,
+    // Optional, how the amount of a payment in the unit is read
+    pub amount_format:   Option<AmountFormat>,
+}
+
+impl DexArgs {
+    /// Parses the lock args with exactly the same rules as the `dex-lock` contract.
+    pub fn from_slice(data: &[u8]) -> Result<Self, ArgsError> {
+        let (owner_lock, setup) = parse_header(data)?;
+
+        // The high 4 bits of setup are the version of the args layout
+        match setup::version(setup) {
+            ARGS_VERSION_0 => Self::parse_v0(data, owner_lock, setup),
+            ARGS_VERSION_1 => Self::parse_v1(data, owner_lock, setup),
+            _ => Err(ArgsError::DexVersionUnknown),
+        }
+    }
+
+    /// Parses the args of version 0 with trailing bytes, which were accepted before the length
+    /// was checked exactly. `from_slice` rejects them, so their cells can only be unlocked by the
+    /// owner.
+    pub fn from_legacy_slice(data: &[u8]) -> Result<Self, ArgsError> {
+        let (owner_lock, setup) = parse_header(data)?;
+        if setup::version(setup) != ARGS_VERSION_0 {
+            return Err(ArgsError::DexVersionUnknown);
+        }
+        let args = Self::parse_base(data, owner_lock, setup)?;
+        if data.len() <= args.extension_pos() {
+            return Err(ArgsError::LockArgsInvalid);
+        }
+        Ok(args)
+    }
+
+    // The args of version 0 end right after the optional hashes
+    fn parse_v0(data: &[u8], owner_lock: Vec<u8>, setup: u8) -> Result<Self, ArgsError> {
+        let args = Self::parse_base(data, owner_lock, setup)?;
+        if data.len() != args.extension_pos() {
+            return Err(ArgsError::LockArgsInvalid);
+        }
+        Ok(args)
+    }
+
+    // Parses the fields shared by all the versions
+    fn parse_base(data: &[u8], owner_lock: Vec<u8>, setup: u8) -> Result<Self, ArgsError> {
+        verify_setup_v0(setup)?;
+        let owner_size = owner_lock.len();
+        let total_value =
+            u128::from_be_bytes(parse_array::<16>(&data[owner_size + 1..owner_size + 17])?);
+
+        let receiver_lock_flag = setup::has_flag(setup, RECEIVER_LOCK);
+        let receiver_lock = get_hash(data, receiver_lock_flag, owner_size + 17)?;
+
+        let unit_type_hash_flag = setup::has_flag(setup, UNIT_TYPE_HASH);
+
+        // unit_type_hash follows receiver_lock if receiver_lock exists
+        let unit_type_hash_pos = if receiver_lock_flag {
+            owner_size + 49
+        } else {
+            owner_size + 17
+        };
+        let unit_type_hash = get_hash(data, unit_type_hash_flag, unit_type_hash_pos)?;
+
+        Ok(DexArgs {
+            owner_lock,
+            setup,
+            total_value,
+            receiver_lock,
+            unit_type_hash,
+            extension: None,
+        })
+    }
+
+    // Version 1 appends the extension to the fields of version 0
+    fn parse_v1(data: &[u8], owner_lock: Vec<u8>, setup: u8) -> Result<Self, ArgsError> {
+        let mut args = Self::parse_base(data, owner_lock, setup)?;
+        let extension_pos = args.extension_pos();
+        let extension = DexExtension::from_slice(&data[extension_pos..])?;
+        verify_setup_v1(&args, &extension)?;
+        args.extension = Some(extension);
+        Ok(args)
+    }
+
+    /// Serializes the lock args, the args must be valid for the version in the setup byte.
+    pub fn to_vec(&self) -> Result<Vec<u8>, ArgsError> {
+        verify_script(&self.owner_lock)?;
+        match self.version() {
+            ARGS_VERSION_0 if self.extension.is_none() => self.to_vec_v0(),
+            ARGS_VERSION_1 => {
+                let extension = self.extension.as_ref().ok_or(ArgsError::LockArgsInvalid)?;
+                verify_setup_v1(self, extension)?;
+                let mut ret = self.to_vec_v0()?;
+                extension.extend_to(&mut ret)?;
+                Ok(ret)
+            }
+            ARGS_VERSION_0 => Err(ArgsError::LockArgsInvalid),
+            _ => Err(ArgsError::DexVersionUnknown),
+        }
+    }
+
+    fn to_vec_v0(&self) -> Result<Vec<u8>, ArgsError> {
+        verify_setup_v0(self.setup)?;
+        let mut ret: Vec<u8> = self.owner_lock.clone();
+        ret.extend([self.setup]);
+        ret.extend(self.total_value.to_be_bytes());
+
+        let receiver_lock_flag = setup::has_flag(self.setup, RECEIVER_LOCK);
+        match (receiver_lock_flag, &self.receiver_lock) {
+            (true, Some(lock)) => ret.extend(lock),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        let unit_type_hash_flag = setup::has_flag(self.setup, UNIT_TYPE_HASH);
+        match (unit_type_hash_flag, &self.unit_type_hash) {
+            (true, Some(unit_hash)) => ret.extend(unit_hash),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        Ok(ret)
+    }
+
+    pub fn version(&self) -> u8 {
+        setup::version(self.setup)
+    }
+
+    pub fn is_udt(&self) -> bool {
+        !setup::has_flag(self.setup, NFT)
+    }
+
+    pub fn is_nft(&self) -> bool {
+        setup::has_flag(self.setup, NFT)
+    }
+
+    pub fn is_partial_fill(&self) -> bool {
+        setup::has_flag(self.setup, PARTIAL_FILL)
+    }
+
+    pub fn is_scoped_cancel(&self) -> bool {
+        self.extension
+            .as_ref()
+            .is_some_and(|extension| extension::has_flag(extension.flags, SCOPED_CANCEL))
+    }
+
+    /// The order is paid together with the other orders of its payee and unit
+    pub fn is_aggregate_payment(&self) -> bool {
+        self.extension
+            .as_ref()
+            .is_some_and(|extension| extension::has_flag(extension.flags, AGGREGATE_PAYMENT))
+    }
+
+    pub fn expiry(&self) -> Option<u64> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.expiry)
+    }
+
+    pub fn nft_type_hash(&self) -> Option<[u8; 32]> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.nft_type_hash)
+    }
+
+    pub fn royalty(&self) -> Option<FeeShare> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.royalty)
+    }
+
+    pub fn platform_fee(&self) -> Option<FeeShare> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.platform_fee)
+    }
+
+    pub fn taker_lock_hash(&self) -> Option<[u8; 32]> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.taker_lock_hash)
+    }
+
+    pub fn bid_type_hash(&self) -> Option<[u8; 32]> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.bid_type_hash)
+    }
+
+    pub fn dutch_auction(&self) -> Option<DutchAuction> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.dutch_auction)
+    }
+
+    pub fn english_auction(&self) -> Option<EnglishAuction> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.english_auction)
+    }
+
+    pub fn bundle(&self) -> Option<Bundle> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.bundle)
+    }
+
+    pub fn asset(&self) -> Option<AssetCommitment> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.asset)
+    }
+
+    /// The script spawned to read the amount of a payment in the unit of `unit_type_hash`, which
+    /// replaces the amount format
+    pub fn amount_reader(&self) -> Option<AmountReader> {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.amount_reader)
+    }
+
+    /// How the amount of a payment in the unit of `unit_type_hash` is read, the sUDT layout
+    /// unless the args tell otherwise
+    pub fn amount_format(&self) -> AmountFormat {
+        self.extension
+            .as_ref()
+            .and_then(|extension| extension.amount_format)
+            .unwrap_or_default()
+    }
+
+    /// The units the order can be paid in, the unit of the order first
+    pub fn accepted_units(&self) -> Vec<AcceptedUnit> {
+        let unit = AcceptedUnit {
+            unit_type_hash: self.unit_type_hash,
+            total_value:    self.total_value,
+        };
+        let alt_units = self
+            .extension
+            .as_ref()
+            .map(|extension| extension.alt_units.as_slice())
+            .unwrap_or_default();
+        [unit]
+            .into_iter()
+            .chain(alt_units.iter().copied())
+            .collect()
+    }
+
+    /// The price to fill the whole order in a transaction whose DEX input has the since, which is
+    /// the total_value unless the order is a Dutch auction
+    pub fn price(&self, input_since: u64) -> u128 {
+        match self.dutch_auction() {
+            Some(auction) => auction.price(self.total_value, input_since),
+            None => self.total_value,
+        }
+    }
+
+    /// A bid locks the payment and is filled by the delivery of the asset of `bid_type_hash`
+    pub fn is_bid(&self) -> bool {
+        self.bid_type_hash().is_some()
+    }
+
+    /// The position of total_value in the serialized args
+    pub fn total_value_pos(&self) -> usize {
+        self.owner_lock.len() + 1
+    }
+
+    /// The position of the extension in the serialized version 1 args
+    pub fn extension_pos(&self) -> usize {
+        let hashes = [self.receiver_lock, self.unit_type_hash]
+            .iter()
+            .filter(|hash| hash.is_some())
+            .count();
+        self.owner_lock.len() + 17 + hashes * 32
+    }
+}
+
+impl DexExtension {
+    // The extension must end exactly after the fields of its flags
+    fn from_slice(data: &[u8]) -> Result<Self, ArgsError> {
+        let mut reader = FieldReader::new(data);
+        let flags = u16::from_be_bytes(reader.read::<FLAGS_SIZE>()?);
+        if flags & !extension::KNOWN_FLAGS != 0 {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+
+        let expiry = reader
+            .read_if::<8>(extension::has_flag(flags, EXPIRY))?
+            .map(u64::from_be_bytes);
+        if expiry.is_some_and(|expiry| !since::is_valid_expiry(expiry)) {
+            return Err(ArgsError::DexExpiryInvalid);
+        }
+        let nft_type_hash = reader.read_if::<32>(extension::has_flag(flags, NFT_TYPE_HASH))?;
+        let royalty = reader
+            .read_if::<SHARE_SIZE>(extension::has_flag(flags, ROYALTY))?
+            .map(FeeShare::from_bytes)
+            .transpose()?;
+        let platform_fee = reader
+            .read_if::<SHARE_SIZE>(extension::has_flag(flags, PLATFORM_FEE))?
+            .map(FeeShare::from_bytes)
+            .transpose()?;
+        let taker_lock_hash = reader.read_if::<32>(extension::has_flag(flags, TAKER_LOCK_HASH))?;
+        let bid_type_hash = reader.read_if::<32>(extension::has_flag(flags, BID))?;
+        let dutch_auction = reader
+            .read_if::<AUCTION_SIZE>(extension::has_flag(flags, DUTCH_AUCTION))?
+            .map(DutchAuction::from_bytes)
+            .transpose()?;
+        let english_auction = reader
+            .read_if::<ENGLISH_AUCTION_SIZE>(extension::has_flag(flags, ENGLISH_AUCTION))?
+            .map(EnglishAuction::from_bytes)
+            .transpose()?;
+        let bundle = reader
+            .read_if::<BUNDLE_SIZE>(extension::has_flag(flags, BUNDLE))?
+            .map(Bundle::from_bytes)
+            .transpose()?;
+        let alt_units = match reader.read_if::<1>(extension::has_flag(flags, ALT_UNITS))? {
+            Some([count]) => (0..count)
+                .map(|_| {
+                    reader
+                        .read::<ACCEPTED_UNIT_SIZE>()
+                        .map(AcceptedUnit::from_bytes)
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        let asset = reader
+            .read_if::<ASSET_COMMITMENT_SIZE>(extension::has_flag(flags, ASSET_COMMITMENT))?
+            .map(AssetCommitment::from_bytes);
+        let amount_reader = reader
+            .read_if::<AMOUNT_READER_SIZE>(extension::has_flag(flags, AMOUNT_READER))?
+            .map(AmountReader::from_bytes)
+            .transpose()?;
+        let amount_format = reader
+            .read_if::<AMOUNT_FORMAT_SIZE>(extension::has_flag(flags, AMOUNT_FORMAT))?
+            .map(AmountFormat::from_bytes)
+            .transpose()?;
+
+        reader.finish()?;
+        Ok(DexExtension {
+            flags,
+            expiry,
+            nft_type_hash,
+            royalty,
+            platform_fee,
+            taker_lock_hash,
+            bid_type_hash,
+            dutch_auction,
+            english_auction,
+            bundle,
+            alt_units,
+            asset,
+            amount_reader,
+            amount_format,
+        })
+    }
+
+    fn extend_to(&self, ret: &mut Vec<u8>) -> Result<(), ArgsError> {
+        if self.flags & !extension::KNOWN_FLAGS != 0 {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+        ret.extend(self.flags.to_be_bytes());
+
+        match (extension::has_flag(self.flags, EXPIRY), self.expiry) {
+            (true, Some(expiry)) if since::is_valid_expiry(expiry) => {
+                ret.extend(expiry.to_be_bytes())
+            }
+            (true, Some(_)) => return Err(ArgsError::DexExpiryInvalid),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, NFT_TYPE_HASH),
+            &self.nft_type_hash,
+        ) {
+            (true, Some(nft_type_hash)) => ret.extend(nft_type_hash),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (extension::has_flag(self.flags, ROYALTY), &self.royalty) {
+            (true, Some(royalty)) => ret.extend(royalty.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, PLATFORM_FEE),
+            &self.platform_fee,
+        ) {
+            (true, Some(platform_fee)) => ret.extend(platform_fee.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, TAKER_LOCK_HASH),
+            &self.taker_lock_hash,
+        ) {
+            (true, Some(taker_lock_hash)) => ret.extend(taker_lock_hash),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (extension::has_flag(self.flags, BID), &self.bid_type_hash) {
+            (true, Some(bid_type_hash)) => ret.extend(bid_type_hash),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, DUTCH_AUCTION),
+            &self.dutch_auction,
+        ) {
+            (true, Some(auction)) => ret.extend(auction.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, ENGLISH_AUCTION),
+            &self.english_auction,
+        ) {
+            (true, Some(auction)) => ret.extend(auction.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (extension::has_flag(self.flags, BUNDLE), &self.bundle) {
+            (true, Some(bundle)) => ret.extend(bundle.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, ALT_UNITS),
+            self.alt_units.len(),
+        ) {
+            (false, 0) => {}
+            (true, count) => {
+                ret.push(u8::try_from(count).map_err(|_| ArgsError::DexSetupInvalid)?);
+                for unit in &self.alt_units {
+                    ret.extend(unit.to_bytes()?);
+                }
+            }
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, ASSET_COMMITMENT),
+            &self.asset,
+        ) {
+            (true, Some(asset)) => ret.extend(asset.to_bytes()),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, AMOUNT_READER),
+            &self.amount_reader,
+        ) {
+            (true, Some(reader)) => ret.extend(reader.to_bytes()?),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        match (
+            extension::has_flag(self.flags, AMOUNT_FORMAT),
+            &self.amount_format,
+        ) {
+            (true, Some(format)) => ret.extend(format.to_bytes()),
+            (false, None) => {}
+            _ => return Err(ArgsError::LockArgsInvalid),
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the lock args of an order and keeps the setup flags consistent with the optional fields.
+#[derive(Debug, Clone)]
+pub struct DexArgsBuilder {
+    args: DexArgs,
+}
+
+impl DexArgsBuilder {
+    /// Starts a fungible token order settled in CKB with the molecule serialized owner lock
+    pub fn new(owner_lock: &[u8], total_value: u128) -> Self {
+        DexArgsBuilder {
+            args: DexArgs {
+                owner_lock: owner_lock.to_vec(),
+                setup: 0,
+                total_value,
+                receiver_lock: None,
+                unit_type_hash: None,
+                extension: None,
+            },
+        }
+    }
+
+    pub fn version(mut self, version: u8) -> Self {
+        self.args.setup = setup::with_version(self.args.setup, version);
+        self
+    }
+
+    pub fn receiver_lock(mut self, receiver_lock_hash: [u8; 32]) -> Self {
+        self.args.setup |= RECEIVER_LOCK;
+        self.args.receiver_lock = Some(receiver_lock_hash);
+        self
+    }
+
+    pub fn unit_type_hash(mut self, unit_type_hash: [u8; 32]) -> Self {
+        self.args.setup |= UNIT_TYPE_HASH;
+        self.args.unit_type_hash = Some(unit_type_hash);
+        self
+    }
+
+    pub fn nft(mut self) -> Self {
+        self.args.setup |= NFT;
+        self
+    }
+
+    pub fn partial_fill(mut self) -> Self {
+        self.args.setup |= PARTIAL_FILL;
+        self
+    }
+
+    /// The order expires at the absolute block number or timestamp in the since format
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= EXPIRY;
+        extension.expiry = Some(expiry);
+        self
+    }
+
+    /// Each order must be cancelled on its own with the owner lock hash in its witness
+    pub fn scoped_cancel(mut self) -> Self {
+        self.extension_mut().flags |= SCOPED_CANCEL;
+        self
+    }
+
+    /// Pays the order by the sum of the outputs of its payee in its unit, together with the other
+    /// orders of the same payee and unit
+    pub fn aggregate_payment(mut self) -> Self {
+        self.extension_mut().flags |= AGGREGATE_PAYMENT;
+        self
+    }
+
+    /// An NFT order of the listed asset with the type script hash
+    pub fn nft_type_hash(mut self, nft_type_hash: [u8; 32]) -> Self {
+        self.args.setup |= NFT;
+        let extension = self.extension_mut();
+        extension.flags |= NFT_TYPE_HASH;
+        extension.nft_type_hash = Some(nft_type_hash);
+        self
+    }
+
+    /// The rate of the royalty is in basis points of the paid value
+    pub fn royalty(mut self, royalty_lock_hash: [u8; 32], rate: u16) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= ROYALTY;
+        extension.royalty = Some(FeeShare {
+            lock_hash: royalty_lock_hash,
+            rate,
+        });
+        self
+    }
+
+    /// The rate of the marketplace fee is in basis points of the paid value
+    pub fn platform_fee(mut self, fee_lock_hash: [u8; 32], rate: u16) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= PLATFORM_FEE;
+        extension.platform_fee = Some(FeeShare {
+            lock_hash: fee_lock_hash,
+            rate,
+        });
+        self
+    }
+
+    /// A private order that only the taker with the lock script hash can fill
+    pub fn taker_lock_hash(mut self, taker_lock_hash: [u8; 32]) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= TAKER_LOCK_HASH;
+        extension.taker_lock_hash = Some(taker_lock_hash);
+        self
+    }
+
+    /// A bid for the asset with the type script hash, the total_value is the wanted amount of a
+    /// fungible token and a bid for an NFT is set with `nft`
+    pub fn bid(mut self, bid_type_hash: [u8; 32]) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= BID;
+        extension.bid_type_hash = Some(bid_type_hash);
+        self
+    }
+
+    /// A Dutch auction from the total_value at `start` down to `end_value` at `end`, both in the
+    /// since format
+    pub fn dutch_auction(mut self, end_value: u128, start: u64, end: u64) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= DUTCH_AUCTION;
+        extension.dutch_auction = Some(DutchAuction {
+            end_value,
+            start,
+            end,
+        });
+        self
+    }
+
+    /// An English auction settled in CKB after the deadline in the since format, the total_value
+    /// is the reserve price
+    pub fn english_auction(mut self, deadline: u64) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= ENGLISH_AUCTION;
+        extension.english_auction = Some(EnglishAuction {
+            deadline,
+            bidder_lock_hash: None,
+        });
+        self
+    }
+
+    /// One of the cells of a bundle, every member has the same args and the total_value is the
+    /// price of the whole bundle
+    pub fn bundle(mut self, bundle_id: [u8; 32], members: u8) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= BUNDLE;
+        extension.bundle = Some(Bundle {
+            id: bundle_id,
+            members,
+        });
+        self
+    }
+
+    /// Accepts the payment in another unit at its own price, None for CKB
+    pub fn alt_unit(mut self, unit_type_hash: Option<[u8; 32]>, total_value: u128) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= ALT_UNITS;
+        extension.alt_units.push(AcceptedUnit {
+            unit_type_hash,
+            total_value,
+        });
+        self
+    }
+
+    /// Commits the order to the listed asset, the type hash (None if the cell has no type) and
+    /// the data hash of the DEX cell
+    pub fn asset(mut self, type_hash: Option<[u8; 32]>, data_hash: [u8; 32]) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= ASSET_COMMITMENT;
+        extension.asset = Some(AssetCommitment {
+            type_hash,
+            data_hash,
+        });
+        self
+    }
+
+    /// Reads the amount of a payment in the unit of `unit_type_hash` by spawning the script with
+    /// the code hash and the hash type from a cell dep
+    pub fn amount_reader(mut self, code_hash: [u8; 32], hash_type: u8) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= AMOUNT_READER;
+        extension.amount_reader = Some(AmountReader {
+            code_hash,
+            hash_type,
+        });
+        self
+    }
+
+    /// Reads the amount of a payment in the unit of `unit_type_hash` in the format
+    pub fn amount_format(mut self, format: AmountFormat) -> Self {
+        let extension = self.extension_mut();
+        extension.flags |= AMOUNT_FORMAT;
+        extension.amount_format = Some(format);
+        self
+    }
+
+    // The fields of the extension only exist in the version 1 args
+    fn extension_mut(&mut self) -> &mut DexExtension {
+        self.args.setup = setup::with_version(self.args.setup, ARGS_VERSION_1);
+        self.args
+            .extension
+            .get_or_insert_with(DexExtension::default)
+    }
+
+    /// Returns the args, which are checked against the rules of the contract
+    pub fn build(self) -> Result<DexArgs, ArgsError> {
+        self.args.to_vec()?;
+        Ok(self.args)
+    }
+}
+
+fn verify_setup_v0(setup: u8) -> Result<(), ArgsError> {
+    // Only fungible tokens can be partially filled
+    if setup::has_flag(setup, NFT) && setup::has_flag(setup, PARTIAL_FILL) {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    Ok(())
+}
+
+fn verify_setup_v1(args: &DexArgs, extension: &DexExtension) -> Result<(), ArgsError> {
+    let setup = args.setup;
+    // Only NFT orders record the type hash of the NFT
+    if extension::has_flag(extension.flags, NFT_TYPE_HASH) && !setup::has_flag(setup, NFT) {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // A bid is filled entirely by the delivery of the asset and pays no shares
+    if extension::has_flag(extension.flags, BID)
+        && (setup::has_flag(setup, PARTIAL_FILL)
+            || extension.flags & (NFT_TYPE_HASH | ROYALTY | PLATFORM_FEE) != 0)
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // The price of a partial fill is proportional to a fixed total_value, and a bid waits for a
+    // fixed amount
+    if extension::has_flag(extension.flags, DUTCH_AUCTION)
+        && (setup::has_flag(setup, PARTIAL_FILL) || extension::has_flag(extension.flags, BID))
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    if extension
+        .dutch_auction
+        .is_some_and(|auction| auction.end_value >= args.total_value)
+    {
+        return Err(ArgsError::DexAuctionInvalid);
+    }
+    // An English auction escrows the bids in CKB and is only settled by them
+    if extension::has_flag(extension.flags, ENGLISH_AUCTION)
+        && (setup::has_flag(setup, UNIT_TYPE_HASH | PARTIAL_FILL)
+            || extension.flags
+                & (EXPIRY | ROYALTY | PLATFORM_FEE | TAKER_LOCK_HASH | BID | DUTCH_AUCTION)
+                != 0)
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // A bundle is settled as a whole by one payment, so its members cannot have their own fate
+    if extension::has_flag(extension.flags, BUNDLE)
+        && (setup::has_flag(setup, PARTIAL_FILL)
+            || extension.flags
+                & (EXPIRY | SCOPED_CANCEL | NFT_TYPE_HASH | BID | DUTCH_AUCTION | ENGLISH_AUCTION)
+                != 0)
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // The units are told apart by the type of the payment, and the partial fill and the auctions
+    // price the order in its own unit
+    if extension::has_flag(extension.flags, ALT_UNITS) {
+        let units: Vec<Option<[u8; 32]>> = [args.unit_type_hash]
+            .into_iter()
+            .chain(extension.alt_units.iter().map(|unit| unit.unit_type_hash))
+            .collect();
+        if extension.alt_units.is_empty()
+            || extension.alt_units.len() > MAX_ALT_UNITS
+            || units
+                .iter()
+                .enumerate()
+                .any(|(i, unit)| units[..i].contains(unit))
+            || setup::has_flag(setup, PARTIAL_FILL)
+            || extension.flags & (BID | DUTCH_AUCTION | ENGLISH_AUCTION) != 0
+        {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+    }
+    // A partial fill re-locks other data, and the members of a bundle hold different assets
+    if extension::has_flag(extension.flags, ASSET_COMMITMENT)
+        && (setup::has_flag(setup, PARTIAL_FILL) || extension::has_flag(extension.flags, BUNDLE))
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // The format describes the one UDT unit of the order, whose payment is not escrowed by a bid.
+    // A run of counted cells would take the outputs of the shares.
+    if let Some(format) = extension.amount_format {
+        if !setup::has_flag(setup, UNIT_TYPE_HASH)
+            || extension.flags & (ALT_UNITS | BID) != 0
+            || (format == AmountFormat::CellCount
+                && extension.flags & (ROYALTY | PLATFORM_FEE) != 0)
+        {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+    }
+    // The reader replaces the format of the one UDT unit of the order, and like the format it is
+    // not used for the escrow of a bid
+    if extension::has_flag(extension.flags, AMOUNT_READER)
+        && (!setup::has_flag(setup, UNIT_TYPE_HASH)
+            || extension.flags & (AMOUNT_FORMAT | ALT_UNITS | BID) != 0)
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // Only the price is paid by the aggregate payment, the other rules tied to the output at the
+    // index of the order cannot be kept
+    if extension::has_flag(extension.flags, AGGREGATE_PAYMENT)
+        && (setup::has_flag(setup, PARTIAL_FILL)
+            || extension.flags
+                & (NFT_TYPE_HASH
+                    | ROYALTY
+                    | PLATFORM_FEE
+                    | BID
+                    | ENGLISH_AUCTION
+                    | BUNDLE
+                    | ALT_UNITS)
+                != 0)
+    {
+        return Err(ArgsError::DexSetupInvalid);
+    }
+    // The shares together cannot exceed the paid value
+    let rates = [extension.royalty, extension.platform_fee]
+        .iter()
+        .flatten()
+        .map(|share| share.rate as u32)
+        .sum::<u32>();
+    if rates > BASIS_POINTS as u32 {
+        return Err(ArgsError::DexRateInvalid);
+    }
+    Ok(())
+}
+
+// Reads the fields of the extension in order
+struct FieldReader<'a> {
+    data: &'a [u8],
+    pos:  usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        FieldReader { data, pos: 0 }
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], ArgsError> {
+        let field = self
+            .data
+            .get(self.pos..self.pos + N)
+            .ok_or(ArgsError::LockArgsInvalid)?;
+        self.pos += N;
+        parse_array::<N>(field)
+    }
+
+    fn read_if<const N: usize>(&mut self, flag: bool) -> Result<Option<[u8; N]>, ArgsError> {
+        if flag {
+            self.read::<N>().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Trailing bytes would give the same order many different lock scripts
+    fn finish(self) -> Result<(), ArgsError> {
+        if self.pos != self.data.len() {
+            return Err(ArgsError::LockArgsInvalid);
+        }
+        Ok(())
+    }
+}
+
+// The owner lock and the setup byte at the start of the args of every version
+fn parse_header(data: &[u8]) -> Result<(Vec<u8>, u8), ArgsError> {
+    if data.len() < MIN_ARGS_SIZE {
+        return Err(ArgsError::LockArgsInvalid);
+    }
+    let owner_size = u32::from_le_bytes(parse_array::<4>(&data[0..4])?) as usize;
+    let required_size = owner_size + 17;
+    if data.len() < required_size {
+        return Err(ArgsError::LockArgsInvalid);
+    }
+
+    verify_script(&data[..owner_size])?;
+    Ok((data[..owner_size].to_vec(), data[owner_size]))
+}
+
+fn get_hash(data: &[u8], flag: bool, from_pos: usize) -> Result<Option<[u8; 32]>, ArgsError> {
+    if !flag {
+        return Ok(None);
+    }
+    if data.len() < from_pos + 32 {
+        Err(ArgsError::LockArgsInvalid)
+    } else {
+        Ok(Some(parse_array::<32>(&data[from_pos..from_pos + 32])?))
+    }
+}
+
+fn parse_array<const N: usize>(arr: &[u8]) -> Result<[u8; N], ArgsError> {
+    arr.try_into().map_err(|_| ArgsError::Encoding)
+}
@@ -0,0 +1,32 @@
+/// The size of a serialized asset commitment, the type script hash and the data hash (32 bytes
+/// each)
+pub const ASSET_COMMITMENT_SIZE: usize = 64;
+
+/// The listed asset recorded in the args, so that the order describes the cell it protects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetCommitment {
+    // the type script hash of the DEX cell, serialized as zeros if it has no type
+    pub type_hash: Option<[u8; 32]>,
+    // the blake2b hash of the data of the DEX cell, the same as the data hash of CKB
+    pub data_hash: [u8; 32],
+}
+
+impl AssetCommitment {
+    pub fn from_bytes(data: [u8; ASSET_COMMITMENT_SIZE]) -> Self {
+        let mut type_hash = [0u8; 32];
+        type_hash.copy_from_slice(&data[..32]);
+        let mut data_hash = [0u8; 32];
+        data_hash.copy_from_slice(&data[32..]);
+        AssetCommitment {
+            type_hash: Some(type_hash).filter(|hash| hash != &[0u8; 32]),
+            data_hash,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; ASSET_COMMITMENT_SIZE] {
+        let mut ret = [0u8; ASSET_COMMITMENT_SIZE];
+        ret[..32].copy_from_slice(&self.type_hash.unwrap_or_default());
+        ret[32..].copy_from_slice(&self.data_hash);
+        ret
+    }
+}
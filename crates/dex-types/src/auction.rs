@@ -0,0 +1,128 @@
+use crate::{math::mul_div_ceil, since, ArgsError};
+
+/// The size of a serialized auction, the end value (`u128` big-endian) and the start and the end
+/// (`u64` big-endian each)
+pub const AUCTION_SIZE: usize = 32;
+
+/// A Dutch auction whose price decays linearly from the total_value of the order at `start` to
+/// `end_value` at `end`. The start and the end are absolute block numbers or timestamps in the
+/// since format with the same metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DutchAuction {
+    pub end_value: u128,
+    pub start:     u64,
+    pub end:       u64,
+}
+
+impl DutchAuction {
+    pub fn from_bytes(data: [u8; AUCTION_SIZE]) -> Result<Self, ArgsError> {
+        let mut end_value = [0u8; 16];
+        end_value.copy_from_slice(&data[..16]);
+        let mut start = [0u8; 8];
+        start.copy_from_slice(&data[16..24]);
+        let mut end = [0u8; 8];
+        end.copy_from_slice(&data[24..]);
+        let auction = DutchAuction {
+            end_value: u128::from_be_bytes(end_value),
+            start:     u64::from_be_bytes(start),
+            end:       u64::from_be_bytes(end),
+        };
+        auction.verify()?;
+        Ok(auction)
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; AUCTION_SIZE], ArgsError> {
+        self.verify()?;
+        let mut ret = [0u8; AUCTION_SIZE];
+        ret[..16].copy_from_slice(&self.end_value.to_be_bytes());
+        ret[16..24].copy_from_slice(&self.start.to_be_bytes());
+        ret[24..].copy_from_slice(&self.end.to_be_bytes());
+        Ok(ret)
+    }
+
+    /// The price proven by the since of the DEX input, rounded up in favour of the seller. A since
+    /// of another metric proves nothing, so the price is the start value.
+    pub fn price(&self, start_value: u128, input_since: u64) -> u128 {
+        let start = self.start & since::VALUE_MASK;
+        let end = self.end & since::VALUE_MASK;
+        let now = if input_since & since::FLAGS_MASK == self.start & since::FLAGS_MASK {
+            (input_since & since::VALUE_MASK).clamp(start, end)
+        } else {
+            start
+        };
+        // The decay of the price cannot exceed start_value - end_value, so it fits in u128
+        let remaining = mul_div_ceil(
+            start_value.saturating_sub(self.end_value),
+            (end - now) as u128,
+            (end - start) as u128,
+        )
+        .unwrap_or(0);
+        self.end_value + remaining
+    }
+
+    // The start and the end must be comparable and the auction must last
+    fn verify(&self) -> Result<(), ArgsError> {
+        if !since::is_valid_expiry(self.start)
+            || self.start & since::FLAGS_MASK != self.end & since::FLAGS_MASK
+            || self.start >= self.end
+        {
+            return Err(ArgsError::DexAuctionInvalid);
+        }
+        Ok(())
+    }
+}
+
+/// The size of a serialized English auction, the deadline (`u64` big-endian) and the lock hash of
+/// the top bidder
+pub const ENGLISH_AUCTION_SIZE: usize = 40;
+
+/// An English auction settled in CKB. The total_value is the reserve price until the first bid
+/// and the top bid after it, and the top bid is escrowed in the capacity of the DEX cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnglishAuction {
+    // the absolute block number or timestamp in the since format after which the auction can be
+    // settled
+    pub deadline:         u64,
+    // the lock script hash of the top bidder, serialized as zeros if there is no bid yet
+    pub bidder_lock_hash: Option<[u8; 32]>,
+}
+
+impl EnglishAuction {
+    pub fn from_bytes(data: [u8; ENGLISH_AUCTION_SIZE]) -> Result<Self, ArgsError> {
+        let mut deadline = [0u8; 8];
+        deadline.copy_from_slice(&data[..8]);
+        let mut bidder_lock_hash = [0u8; 32];
+        bidder_lock_hash.copy_from_slice(&data[8..]);
+        let auction = EnglishAuction {
+            deadline:         u64::from_be_bytes(deadline),
+            bidder_lock_hash: Some(bidder_lock_hash).filter(|hash| hash != &[0u8; 32]),
+        };
+        auction.verify()?;
+        Ok(auction)
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; ENGLISH_AUCTION_SIZE], ArgsError> {
+        self.verify()?;
+        let mut ret = [0u8; ENGLISH_AUCTION_SIZE];
+        ret[..8].copy_from_slice(&self.deadline.to_be_bytes());
+        ret[8..].copy_from_slice(&self.bidder_lock_hash.unwrap_or_default());
+        Ok(ret)
+    }
+
+    /// The bid escrowed in the capacity of the DEX cell
+    pub fn escrow(&self, total_value: u128) -> u128 {
+        if self.bidder_lock_hash.is_some() {
+            total_value
+        } else {
+            0
+        }
+    }
+
+    // The all-zero hash means no bid, so it cannot be the hash of a bidder
+    fn verify(&self) -> Result<(), ArgsError> {
+        if !since::is_valid_expiry(self.deadline) || self.bidder_lock_hash == Some([0u8; 32]) {
+            return Err(ArgsError::DexAuctionInvalid);
+        }
+        Ok(())
+    }
+}
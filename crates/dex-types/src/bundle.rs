@@ -0,0 +1,42 @@
+use crate::ArgsError;
+
+/// The size of a serialized bundle, the id (32 bytes) and the number of members (1 byte)
+pub const BUNDLE_SIZE: usize = 33;
+
+/// Several DEX cells with identical args sold together for the total_value. The id tells apart
+/// the bundles of the same seller at the same price, such as a hash of an input of the listing
+/// transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bundle {
+    pub id:      [u8; 32],
+    pub members: u8,
+}
+
+impl Bundle {
+    pub fn from_bytes(data: [u8; BUNDLE_SIZE]) -> Result<Self, ArgsError> {
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&data[..32]);
+        let bundle = Bundle {
+            id,
+            members: data[32],
+        };
+        bundle.verify()?;
+        Ok(bundle)
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; BUNDLE_SIZE], ArgsError> {
+        self.verify()?;
+        let mut ret = [0u8; BUNDLE_SIZE];
+        ret[..32].copy_from_slice(&self.id);
+        ret[32] = self.members;
+        Ok(ret)
+    }
+
+    // A bundle sells at least two cells
+    fn verify(&self) -> Result<(), ArgsError> {
+        if self.members < 2 {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+        Ok(())
+    }
+}
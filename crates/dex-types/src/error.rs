@@ -0,0 +1,48 @@
+use core::fmt;
+
+use dex_errors::DexLockError;
+
+/// Errors of building or parsing the DEX lock args, each of them maps to the error code of the
+/// `dex-lock` contract with the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    LockArgsInvalid,
+    Encoding,
+    DexSetupInvalid,
+    DexVersionUnknown,
+    DexExpiryInvalid,
+    DexRateInvalid,
+    DexAuctionInvalid,
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::LockArgsInvalid => "the length of the lock args does not match the setup",
+            Self::Encoding => "the owner lock is not a valid molecule script",
+            Self::DexSetupInvalid => "the setup byte contains an invalid combination of flags",
+            Self::DexVersionUnknown => "the version of the lock args is unknown",
+            Self::DexExpiryInvalid => "the expiry is not an absolute block number or timestamp",
+            Self::DexRateInvalid => "the rate of a share is zero or more than 10000 basis points",
+            Self::DexAuctionInvalid => "the auction does not decay over a valid period",
+        };
+        f.write_str(reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArgsError {}
+
+impl From<ArgsError> for DexLockError {
+    fn from(err: ArgsError) -> Self {
+        match err {
+            ArgsError::LockArgsInvalid => Self::LockArgsInvalid,
+            ArgsError::Encoding => Self::Encoding,
+            ArgsError::DexSetupInvalid => Self::DexSetupInvalid,
+            ArgsError::DexVersionUnknown => Self::DexVersionUnknown,
+            ArgsError::DexExpiryInvalid => Self::DexExpiryInvalid,
+            ArgsError::DexRateInvalid => Self::DexRateInvalid,
+            ArgsError::DexAuctionInvalid => Self::DexAuctionInvalid,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Flags of the extension of the version 1 args. The extension starts with the flags (`u16`
+//! big-endian) and each flag adds an optional field after them in the order of the bits.
+
+/// `expiry` exists, and after it anyone can return the order to the owner. It is not a deadline
+/// for takers, who can still fill the order until it is returned
+pub const EXPIRY: u16 = 0b0000_0000_0000_0001;
+/// The owner can only cancel the orders marked by the owner lock hash in their witnesses, instead
+/// of all the orders in a transaction with an input locked by the owner_lock
+pub const SCOPED_CANCEL: u16 = 0b0000_0000_0000_0010;
+
+/// `nft_type_hash` exists, the type hash of the listed NFT, only for NFT orders
+pub const NFT_TYPE_HASH: u16 = 0b0000_0000_0000_0100;
+
+/// `royalty` exists, a share of the paid value goes to the royalty receiver
+pub const ROYALTY: u16 = 0b0000_0000_0000_1000;
+
+/// `platform_fee` exists, a share of the paid value goes to the marketplace fee receiver
+pub const PLATFORM_FEE: u16 = 0b0000_0000_0001_0000;
+
+/// `taker_lock_hash` exists, only the taker with the lock hash can fill the order
+pub const TAKER_LOCK_HASH: u16 = 0b0000_0000_0010_0000;
+
+/// `bid_type_hash` exists and the order is a bid, the DEX cell holds the payment in CKB or the
+/// UDT of `unit_type_hash` and waits for the asset with the type hash. The setup byte has no spare
+/// flag, so the bid mode lives in the extension.
+pub const BID: u16 = 0b0000_0000_0100_0000;
+
+/// `dutch_auction` exists, the price decays from the total_value over time
+pub const DUTCH_AUCTION: u16 = 0b0000_0000_1000_0000;
+
+/// `english_auction` exists, the order is sold to the highest bid after a deadline
+pub const ENGLISH_AUCTION: u16 = 0b0000_0001_0000_0000;
+
+/// `bundle` exists, the cells with the same args are only sold together
+pub const BUNDLE: u16 = 0b0000_0010_0000_0000;
+
+/// `alt_units` exists, the order can also be paid in any one of the units at its own price. The
+/// field is the number of units (`u8`) followed by the units.
+pub const ALT_UNITS: u16 = 0b0000_0100_0000_0000;
+
+/// `asset` exists, the type hash and the data hash of the DEX cell, verified before the order is
+/// taken
+pub const ASSET_COMMITMENT: u16 = 0b0000_1000_0000_0000;
+
+/// `amount_reader` exists, the amount of a payment in the unit of `unit_type_hash` is read by
+/// spawning the reader script from a cell dep instead of the sUDT layout
+pub const AMOUNT_READER: u16 = 0b0001_0000_0000_0000;
+
+/// `amount_format` exists, how the amount of a payment in the unit of `unit_type_hash` is read
+/// instead of the sUDT layout
+pub const AMOUNT_FORMAT: u16 = 0b0010_0000_0000_0000;
+
+/// The order is paid by the sum of the outputs of its payee in its unit instead of the output at
+/// its index, together with the other orders of the same payee and unit in the transaction
+pub const AGGREGATE_PAYMENT: u16 = 0b0100_0000_0000_0000;
+
+/// Unknown flags are rejected, so that a flag can be given a meaning later
+pub const KNOWN_FLAGS: u16 = EXPIRY
+    | SCOPED_CANCEL
+    | NFT_TYPE_HASH
+    | ROYALTY
+    | PLATFORM_FEE
+    | TAKER_LOCK_HASH
+    | BID
+    | DUTCH_AUCTION
+    | ENGLISH_AUCTION
+    | BUNDLE
+    | ALT_UNITS
+    | ASSET_COMMITMENT
+    | AMOUNT_READER
+    | AMOUNT_FORMAT
+    | AGGREGATE_PAYMENT;
+
+pub const FLAGS_SIZE: usize = 2;
+
+pub fn has_flag(flags: u16, flag: u16) -> bool {
+    flags & flag != 0
+}
@@ -0,0 +1,35 @@
+//! Shared types of the Simple DEX Lock (SDL).
+//!
+//! The crate is `no_std` so that both the on-chain `dex-lock` contract and off-chain services
+//! build and parse the lock args with the same code. Enable the `std` feature to implement
+//! `std::error::Error` for [`ArgsError`].
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod amount;
+mod args;
+mod asset;
+mod auction;
+mod bundle;
+mod error;
+pub mod extension;
+pub mod math;
+mod reader;
+mod script;
+pub mod setup;
+mod share;
+pub mod since;
+mod unit;
+pub mod witness;
+
+pub use amount::{AmountFormat, AMOUNT_FORMAT_SIZE, UDT_AMOUNT_LEN};
+pub use args::{DexArgs, DexArgsBuilder, DexExtension, MIN_ARGS_SIZE};
+pub use asset::{AssetCommitment, ASSET_COMMITMENT_SIZE};
+pub use auction::{DutchAuction, EnglishAuction, AUCTION_SIZE, ENGLISH_AUCTION_SIZE};
+pub use bundle::{Bundle, BUNDLE_SIZE};
+pub use error::ArgsError;
+pub use reader::{AmountReader, AMOUNT_READER_SIZE, READER_SOURCE_INPUT, READER_SOURCE_OUTPUT};
+pub use script::verify_script;
+pub use share::{FeeShare, BASIS_POINTS, SHARE_SIZE};
+pub use unit::{AcceptedUnit, ACCEPTED_UNIT_SIZE, MAX_ALT_UNITS};
@@ -0,0 +1,44 @@
+//! 256-bit intermediate arithmetic for the prices, which are `u128` values of CKB or UDT.
+
+/// Calculates ceil(a * b / c) with a 256-bit intermediate product, returns None if c is zero or
+/// the result does not fit in u128
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    let (high, mut low) = mul_wide(a, b);
+    if high >= c {
+        return None;
+    }
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for _ in 0..128 {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | (low >> 127);
+        low <<= 1;
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    if remainder == 0 {
+        Some(quotient)
+    } else {
+        quotient.checked_add(1)
+    }
+}
+
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let high_high = a_high * b_high;
+    let middle = (low_low >> 64) + (low_high & MASK) + (high_low & MASK);
+    let low = (middle << 64) | (low_low & MASK);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    (high, low)
+}
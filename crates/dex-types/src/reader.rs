@@ -0,0 +1,52 @@
+use crate::ArgsError;
+
+/// The size of a serialized amount reader, the code hash (32 bytes) and the hash type (1 byte)
+pub const AMOUNT_READER_SIZE: usize = 33;
+
+/// `argv[1]` of the amount reader when the cell is an input
+pub const READER_SOURCE_INPUT: &str = "input";
+/// `argv[1]` of the amount reader when the cell is an output
+pub const READER_SOURCE_OUTPUT: &str = "output";
+
+/// The script spawned from a cell dep to read the amount of a cell of the unit type, for the
+/// units whose cells do not have the sUDT layout.
+///
+/// The reader is spawned with the index of the cell in decimal as `argv[0]` and its source as
+/// `argv[1]`, and inherits the write end of a pipe as its only fd. It writes the amount to the
+/// pipe as a little-endian `u128` and exits with 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountReader {
+    // the code hash of the reader, matched against the cell deps like a script
+    pub code_hash: [u8; 32],
+    // the hash type of the code hash, 0 (data), 1 (type), 2 (data1) or 4 (data2)
+    pub hash_type: u8,
+}
+
+impl AmountReader {
+    pub fn from_bytes(data: [u8; AMOUNT_READER_SIZE]) -> Result<Self, ArgsError> {
+        let mut code_hash = [0u8; 32];
+        code_hash.copy_from_slice(&data[..32]);
+        let reader = AmountReader {
+            code_hash,
+            hash_type: data[32],
+        };
+        reader.verify()?;
+        Ok(reader)
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; AMOUNT_READER_SIZE], ArgsError> {
+        self.verify()?;
+        let mut ret = [0u8; AMOUNT_READER_SIZE];
+        ret[..32].copy_from_slice(&self.code_hash);
+        ret[32] = self.hash_type;
+        Ok(ret)
+    }
+
+    // The hash types of a CKB script
+    fn verify(&self) -> Result<(), ArgsError> {
+        if !matches!(self.hash_type, 0 | 1 | 2 | 4) {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+        Ok(())
+    }
+}
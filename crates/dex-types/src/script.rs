@@ -0,0 +1,47 @@
+use crate::ArgsError;
+
+const SCRIPT_FIELD_COUNT: usize = 3;
+const CODE_HASH_SIZE: usize = 32;
+const HASH_TYPE_SIZE: usize = 1;
+
+fn read_u32(data: &[u8], pos: usize) -> usize {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&data[pos..pos + 4]);
+    u32::from_le_bytes(buf) as usize
+}
+
+/// Verifies that the slice is a molecule serialized `Script` table, with the same rules as
+/// `Script::from_slice` of `ckb-types`.
+pub fn verify_script(data: &[u8]) -> Result<(), ArgsError> {
+    if data.len() < 4 || read_u32(data, 0) != data.len() {
+        return Err(ArgsError::Encoding);
+    }
+    if data.len() < 8 {
+        return Err(ArgsError::Encoding);
+    }
+    let first_offset = read_u32(data, 4);
+    if first_offset & 0b11 != 0 || first_offset < 8 || data.len() < first_offset {
+        return Err(ArgsError::Encoding);
+    }
+    if first_offset / 4 - 1 != SCRIPT_FIELD_COUNT {
+        return Err(ArgsError::Encoding);
+    }
+    let offsets = [
+        read_u32(data, 4),
+        read_u32(data, 8),
+        read_u32(data, 12),
+        data.len(),
+    ];
+    if offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(ArgsError::Encoding);
+    }
+    if offsets[1] - offsets[0] != CODE_HASH_SIZE || offsets[2] - offsets[1] != HASH_TYPE_SIZE {
+        return Err(ArgsError::Encoding);
+    }
+    // args is a molecule fixvec of bytes
+    let script_args = &data[offsets[2]..offsets[3]];
+    if script_args.len() < 4 || read_u32(script_args, 0) + 4 != script_args.len() {
+        return Err(ArgsError::Encoding);
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Flags of the setup byte. The low 4 bits are flags and the high 4 bits are the version of the
+//! args layout.
+
+/// `receiver_lock` exists, otherwise the receiver is the `owner_lock`
+pub const RECEIVER_LOCK: u8 = 0b0000_0001;
+/// `unit_type_hash` exists and the order is settled in the UDT, otherwise in CKB
+pub const UNIT_TYPE_HASH: u8 = 0b0000_0010;
+/// The listed asset is a non-fungible token, otherwise a fungible token
+pub const NFT: u8 = 0b0000_0100;
+/// The fungible token order can be partially filled
+pub const PARTIAL_FILL: u8 = 0b0000_1000;
+
+pub const FLAGS_MASK: u8 = 0b0000_1111;
+pub const VERSION_SHIFT: u8 = 4;
+
+pub const ARGS_VERSION_0: u8 = 0;
+/// The version 0 args followed by the extension
+pub const ARGS_VERSION_1: u8 = 1;
+
+pub fn version(setup: u8) -> u8 {
+    setup >> VERSION_SHIFT
+}
+
+pub fn with_version(setup: u8, version: u8) -> u8 {
+    (setup & FLAGS_MASK) | (version << VERSION_SHIFT)
+}
+
+pub fn has_flag(setup: u8, flag: u8) -> bool {
+    setup & flag != 0
+}
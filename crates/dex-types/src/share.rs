@@ -0,0 +1,51 @@
+use crate::ArgsError;
+
+/// The rates of the shares are in basis points of the paid value
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// The size of a serialized share, the lock hash (32 bytes) and the rate (`u16` big-endian)
+pub const SHARE_SIZE: usize = 34;
+
+/// A share of the value paid for an order that goes to another lock, such as a creator royalty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeShare {
+    // the lock script hash of the receiver of the share
+    pub lock_hash: [u8; 32],
+    pub rate:      u16,
+}
+
+impl FeeShare {
+    pub fn from_bytes(data: [u8; SHARE_SIZE]) -> Result<Self, ArgsError> {
+        let mut lock_hash = [0u8; 32];
+        lock_hash.copy_from_slice(&data[..32]);
+        let share = FeeShare {
+            lock_hash,
+            rate: u16::from_be_bytes([data[32], data[33]]),
+        };
+        share.verify()?;
+        Ok(share)
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; SHARE_SIZE], ArgsError> {
+        self.verify()?;
+        let mut ret = [0u8; SHARE_SIZE];
+        ret[..32].copy_from_slice(&self.lock_hash);
+        ret[32..].copy_from_slice(&self.rate.to_be_bytes());
+        Ok(ret)
+    }
+
+    /// The share of the value rounded down, the rest of the value goes to the seller
+    pub fn amount(&self, value: u128) -> u128 {
+        let rate = self.rate as u128;
+        let basis_points = BASIS_POINTS as u128;
+        // Splits the value to avoid overflowing u128
+        value / basis_points * rate + value % basis_points * rate / basis_points
+    }
+
+    fn verify(&self) -> Result<(), ArgsError> {
+        if self.rate == 0 || self.rate > BASIS_POINTS {
+            return Err(ArgsError::DexRateInvalid);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! The `since` field of a CKB cell input, whose high 8 bits are flags and low 56 bits are the
+//! value. Only absolute block numbers and timestamps can be compared as integers.
+
+pub const RELATIVE_FLAG: u64 = 1 << 63;
+pub const METRIC_BLOCK_NUMBER: u64 = 0;
+pub const METRIC_EPOCH: u64 = 0b0010_0000 << 56;
+pub const METRIC_TIMESTAMP: u64 = 0b0100_0000 << 56;
+pub const FLAGS_MASK: u64 = 0xff << 56;
+pub const VALUE_MASK: u64 = !FLAGS_MASK;
+
+pub fn absolute_block_number(block_number: u64) -> u64 {
+    METRIC_BLOCK_NUMBER | (block_number & VALUE_MASK)
+}
+
+/// The median timestamp of the previous 37 blocks in seconds
+pub fn absolute_timestamp(timestamp: u64) -> u64 {
+    METRIC_TIMESTAMP | (timestamp & VALUE_MASK)
+}
+
+/// An expiry must be an absolute block number or an absolute timestamp
+pub fn is_valid_expiry(expiry: u64) -> bool {
+    let flags = expiry & FLAGS_MASK;
+    flags == METRIC_BLOCK_NUMBER || flags == METRIC_TIMESTAMP
+}
+
+/// Returns true if an input with the since can only be committed at or after the expiry
+pub fn is_expired(expiry: u64, since: u64) -> bool {
+    since & FLAGS_MASK == expiry & FLAGS_MASK && since & VALUE_MASK >= expiry & VALUE_MASK
+}
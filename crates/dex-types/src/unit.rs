@@ -0,0 +1,40 @@
+use crate::ArgsError;
+
+/// The size of a serialized accepted unit, the unit type hash (32 bytes, zeros for CKB) and the
+/// total value (`u128` big-endian)
+pub const ACCEPTED_UNIT_SIZE: usize = 48;
+
+/// The maximum number of units accepted besides the unit of the order
+pub const MAX_ALT_UNITS: usize = 3;
+
+/// A unit the order can be paid in and the price in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptedUnit {
+    // the type script hash of the UDT, or None for CKB
+    pub unit_type_hash: Option<[u8; 32]>,
+    pub total_value:    u128,
+}
+
+impl AcceptedUnit {
+    pub fn from_bytes(data: [u8; ACCEPTED_UNIT_SIZE]) -> Self {
+        let mut unit_type_hash = [0u8; 32];
+        unit_type_hash.copy_from_slice(&data[..32]);
+        let mut total_value = [0u8; 16];
+        total_value.copy_from_slice(&data[32..]);
+        AcceptedUnit {
+            unit_type_hash: Some(unit_type_hash).filter(|hash| hash != &[0u8; 32]),
+            total_value:    u128::from_be_bytes(total_value),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<[u8; ACCEPTED_UNIT_SIZE], ArgsError> {
+        // The all-zero hash means CKB, so it cannot be the hash of a UDT
+        if self.unit_type_hash == Some([0u8; 32]) {
+            return Err(ArgsError::DexSetupInvalid);
+        }
+        let mut ret = [0u8; ACCEPTED_UNIT_SIZE];
+        ret[..32].copy_from_slice(&self.unit_type_hash.unwrap_or_default());
+        ret[32..].copy_from_slice(&self.total_value.to_be_bytes());
+        Ok(ret)
+    }
+}
@@ -0,0 +1,31 @@
+//! Marks of the owner in the `lock` field of the witness (`WitnessArgs`) of a DEX input. A mark
+//! is only valid with an input locked by the owner_lock in the same transaction.
+
+/// The size of a cancel mark, the owner lock hash
+pub const CANCEL_MARK_SIZE: usize = 32;
+/// The size of a reprice mark, the owner lock hash followed by [`REPRICE`]
+pub const REPRICE_MARK_SIZE: usize = 33;
+/// The byte after the owner lock hash in a reprice mark
+pub const REPRICE: u8 = 0x01;
+
+/// Marks an order with a scoped cancel to be cancelled
+pub fn cancel_mark(owner_lock_hash: [u8; 32]) -> [u8; CANCEL_MARK_SIZE] {
+    owner_lock_hash
+}
+
+/// Marks an order to be re-locked at the same index with only a new total_value
+pub fn reprice_mark(owner_lock_hash: [u8; 32]) -> [u8; REPRICE_MARK_SIZE] {
+    let mut ret = [REPRICE; REPRICE_MARK_SIZE];
+    ret[..32].copy_from_slice(&owner_lock_hash);
+    ret
+}
+
+/// Returns the owner lock hash of a reprice mark
+pub fn parse_reprice_mark(mark: &[u8]) -> Option<[u8; 32]> {
+    if mark.len() != REPRICE_MARK_SIZE || mark[32] != REPRICE {
+        return None;
+    }
+    let mut owner_lock_hash = [0u8; 32];
+    owner_lock_hash.copy_from_slice(&mark[..32]);
+    Some(owner_lock_hash)
+}
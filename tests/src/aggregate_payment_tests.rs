@@ -0,0 +1,512 @@
+use dex_errors::DexLockError;
+use dex_types::{AmountFormat, DexArgsBuilder, BASIS_POINTS};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// the prices of the two CKB orders of the first owner and the UDT order of the second one
+const FIRST_PRICE: u64 = 500_0000_0000;
+const SECOND_PRICE: u64 = 700_0000_0000;
+const UDT_PRICE: u128 = 300_0000_0000;
+// the price of an order of the first owner paid at its own index
+const POSITIONAL_PRICE: u64 = 200_0000_0000;
+const OWNER_CAPACITY: u64 = 1000_0000_0000;
+// the first owner takes half of the price of an order of a third owner as its platform fee, the
+// same value as its own orders
+const FEE_RATE: u16 = 5000;
+const FEE_ORDER_PRICE: u64 = 2 * (FIRST_PRICE + SECOND_PRICE);
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Underpaid,
+    // the UDT order is paid in CKB
+    UnitNotMatch,
+    // an order of the first owner is paid at its own index in the same batch
+    PositionalOrder,
+    // the payment of the order paid at its own index also covers the other orders
+    PositionalOutputCounted,
+    // the first owner takes the platform fee of an order of a third owner in the same batch
+    ShareOutput,
+    // the platform fee output of the order of the third owner also pays the orders of the first
+    // owner
+    ShareOutputCounted,
+    // the first owner spends a cell and gets its capacity back with the payment
+    OwnerInput,
+    // the capacity of the cell spent by the first owner is counted as the payment
+    OwnerInputNotRefunded,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let mut random_lock = || {
+        context
+            .build_script(
+                &always_success_out_point,
+                rng.gen::<[u8; 20]>().to_vec().into(),
+            )
+            .expect("script")
+    };
+    let first_owner_lock = random_lock();
+    let second_owner_lock = random_lock();
+    let third_owner_lock = random_lock();
+    let buyer_lock = random_lock();
+
+    // An always success type script plays the UDT
+    let unit_type = context
+        .build_script(&always_success_out_point, Bytes::from(vec![42]))
+        .expect("script");
+    let mut first_owner_lock_hash = [0u8; 32];
+    first_owner_lock_hash.copy_from_slice(first_owner_lock.calc_script_hash().as_slice());
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(unit_type.calc_script_hash().as_slice());
+
+    // The orders of the first owner have different prices, so they are in different script groups
+    let first_args = |price: u64| {
+        DexArgsBuilder::new(first_owner_lock.as_slice(), price as u128)
+            .nft()
+            .scoped_cancel()
+            .aggregate_payment()
+            .build()
+            .unwrap()
+    };
+    let mut orders = vec![
+        first_args(FIRST_PRICE),
+        DexArgsBuilder::new(second_owner_lock.as_slice(), UDT_PRICE)
+            .nft()
+            .unit_type_hash(unit_type_hash)
+            .aggregate_payment()
+            .build()
+            .unwrap(),
+        first_args(SECOND_PRICE),
+    ];
+    if matches!(
+        error,
+        DexError::PositionalOrder | DexError::PositionalOutputCounted
+    ) {
+        orders.push(
+            DexArgsBuilder::new(first_owner_lock.as_slice(), POSITIONAL_PRICE as u128)
+                .nft()
+                .build()
+                .unwrap(),
+        );
+    }
+    if matches!(error, DexError::ShareOutput | DexError::ShareOutputCounted) {
+        orders.push(
+            DexArgsBuilder::new(third_owner_lock.as_slice(), FEE_ORDER_PRICE as u128)
+                .nft()
+                .platform_fee(first_owner_lock_hash, FEE_RATE)
+                .build()
+                .unwrap(),
+        );
+    }
+
+    let dex_capacity = 300_0000_0000u64;
+    let mut inputs = vec![];
+    let mut asset_types = vec![];
+    for (order, args) in orders.iter().enumerate() {
+        let asset_type = ScriptBuilder::default()
+            .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+            .hash_type(Byte::from(2u8))
+            .args(Bytes::from(vec![order as u8]).pack())
+            .build();
+        let dex_lock_script = context
+            .build_script(&dex_out_point, args.to_vec().unwrap().into())
+            .expect("script");
+        let dex_input_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(dex_lock_script)
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            Bytes::new(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(dex_input_out_point)
+                .build(),
+        );
+        asset_types.push(asset_type);
+    }
+
+    let buyer_token = 1000_0000_0000u128;
+    let mut input_cells = vec![
+        (
+            CellOutput::new_builder()
+                .capacity(5000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        ),
+        (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(unit_type.clone()).pack())
+                .build(),
+            Bytes::from(buyer_token.to_le_bytes().to_vec()),
+        ),
+    ];
+    if matches!(
+        error,
+        DexError::OwnerInput | DexError::OwnerInputNotRefunded
+    ) {
+        input_cells.push((
+            CellOutput::new_builder()
+                .capacity(OWNER_CAPACITY.pack())
+                .lock(first_owner_lock.clone())
+                .build(),
+            Bytes::new(),
+        ));
+    }
+    for (output, data) in input_cells {
+        let out_point = context.create_cell(output, data);
+        inputs.push(CellInput::new_builder().previous_output(out_point).build());
+    }
+
+    let ckb_output = |lock: &Script, capacity: u64| {
+        (
+            CellOutput::new_builder()
+                .capacity(capacity.pack())
+                .lock(lock.clone())
+                .build(),
+            Bytes::new(),
+        )
+    };
+    let udt_output = |lock: &Script, amount: u128| {
+        (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(lock.clone())
+                .type_(Some(unit_type.clone()).pack())
+                .build(),
+            Bytes::from(amount.to_le_bytes().to_vec()),
+        )
+    };
+
+    // The NFTs of the aggregate orders take the outputs at their indexes, where the one-to-one
+    // rule would want the payments
+    let mut outputs = vec![];
+    for asset_type in asset_types.iter().take(3) {
+        outputs.push((
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            Bytes::new(),
+        ));
+    }
+    let first_value = FIRST_PRICE + SECOND_PRICE;
+    let fee_value = FEE_ORDER_PRICE * FEE_RATE as u64 / BASIS_POINTS as u64;
+    match error {
+        DexError::PositionalOrder => {
+            outputs.push(ckb_output(&first_owner_lock, POSITIONAL_PRICE));
+            outputs.push(ckb_output(&first_owner_lock, first_value));
+        }
+        DexError::PositionalOutputCounted => {
+            outputs.push(ckb_output(
+                &first_owner_lock,
+                POSITIONAL_PRICE + first_value,
+            ));
+        }
+        DexError::ShareOutput => {
+            outputs.push(ckb_output(&third_owner_lock, FEE_ORDER_PRICE - fee_value));
+            outputs.push(ckb_output(&first_owner_lock, fee_value));
+            outputs.push(ckb_output(&first_owner_lock, first_value));
+        }
+        DexError::ShareOutputCounted => {
+            outputs.push(ckb_output(&third_owner_lock, FEE_ORDER_PRICE - fee_value));
+            outputs.push(ckb_output(&first_owner_lock, fee_value));
+        }
+        DexError::Underpaid => outputs.push(ckb_output(&first_owner_lock, first_value - 1)),
+        DexError::OwnerInput => {
+            outputs.push(ckb_output(&first_owner_lock, first_value + OWNER_CAPACITY))
+        }
+        _ => outputs.push(ckb_output(&first_owner_lock, first_value)),
+    }
+    if error == DexError::UnitNotMatch {
+        outputs.push(ckb_output(&second_owner_lock, UDT_PRICE as u64));
+        outputs.push(udt_output(&buyer_lock, buyer_token));
+    } else {
+        outputs.push(udt_output(&second_owner_lock, UDT_PRICE));
+        outputs.push(udt_output(&buyer_lock, buyer_token - UDT_PRICE));
+    }
+    if let Some(asset_type) = asset_types.get(3) {
+        outputs.push((
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            Bytes::new(),
+        ));
+    }
+    outputs.push(ckb_output(&buyer_lock, 1000_0000_0000));
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    let (outputs, outputs_data): (Vec<_>, Vec<_>) = outputs.into_iter().unzip();
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+// An order of counted cells paid at its own index at input 0 and an aggregate order of the same
+// owner and unit at input 3. The run of the first order takes the outputs 0 to 2, and the
+// aggregate order is paid by the cells of the owner after them.
+fn create_counted_cells_context(aggregate_cells: usize) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let mut random_lock = || {
+        context
+            .build_script(
+                &always_success_out_point,
+                rng.gen::<[u8; 20]>().to_vec().into(),
+            )
+            .expect("script")
+    };
+    let owner_lock = random_lock();
+    let buyer_lock = random_lock();
+
+    // An always success type script plays the counted cells
+    let unit_type = context
+        .build_script(&always_success_out_point, Bytes::from(vec![42]))
+        .expect("script");
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(unit_type.calc_script_hash().as_slice());
+
+    let counted_args = |price: u128| {
+        DexArgsBuilder::new(owner_lock.as_slice(), price)
+            .nft()
+            .unit_type_hash(unit_type_hash)
+            .amount_format(AmountFormat::CellCount)
+    };
+    let orders = [
+        counted_args(3).build().unwrap(),
+        counted_args(2).aggregate_payment().build().unwrap(),
+    ];
+
+    let cell_capacity = 300_0000_0000u64;
+    let cell = |lock: &Script, type_: Option<Script>| {
+        CellOutput::new_builder()
+            .capacity(cell_capacity.pack())
+            .lock(lock.clone())
+            .type_(type_.pack())
+            .build()
+    };
+    let mut inputs = vec![];
+    let mut asset_types = vec![];
+    for (order, args) in orders.iter().enumerate() {
+        let asset_type = context
+            .build_script(&always_success_out_point, Bytes::from(vec![order as u8]))
+            .expect("script");
+        let dex_lock_script = context
+            .build_script(&dex_out_point, args.to_vec().unwrap().into())
+            .expect("script");
+        let dex_input_out_point = context.create_cell(
+            cell(&dex_lock_script, Some(asset_type.clone())),
+            Bytes::new(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(dex_input_out_point)
+                .build(),
+        );
+        asset_types.push(asset_type);
+    }
+    // The buyer cells fill the inputs 1 and 2
+    for _ in 0..2 {
+        let out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(5000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        );
+        inputs.insert(
+            inputs.len() - 1,
+            CellInput::new_builder().previous_output(out_point).build(),
+        );
+    }
+
+    let mut outputs = vec![];
+    for _ in 0..3 {
+        outputs.push(cell(&owner_lock, Some(unit_type.clone())));
+    }
+    for asset_type in asset_types {
+        outputs.push(cell(&buyer_lock, Some(asset_type)));
+    }
+    for _ in 0..aggregate_cells {
+        outputs.push(cell(&owner_lock, Some(unit_type.clone())));
+    }
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(1000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+    );
+    let outputs_data = vec![Bytes::new(); outputs.len()];
+
+    let witnesses = vec![Bytes::new(); inputs.len()];
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_aggregate_payment_mixed_owners_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_aggregate_payment_with_positional_order_success() {
+    let (context, tx) = create_test_context(DexError::PositionalOrder);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_aggregate_payment_with_share_output_success() {
+    let (context, tx) = create_test_context(DexError::ShareOutput);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_aggregate_payment_owner_input_success() {
+    let (context, tx) = create_test_context(DexError::OwnerInput);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_aggregate_payment_underpaid_error() {
+    let (context, tx) = create_test_context(DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_aggregate_payment_unit_not_match_error() {
+    let (context, tx) = create_test_context(DexError::UnitNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_aggregate_payment_positional_output_counted_error() {
+    let (context, tx) = create_test_context(DexError::PositionalOutputCounted);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_aggregate_payment_owner_input_not_refunded_error() {
+    let (context, tx) = create_test_context(DexError::OwnerInputNotRefunded);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_aggregate_payment_share_output_counted_error() {
+    let (context, tx) = create_test_context(DexError::ShareOutputCounted);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_aggregate_payment_with_counted_order_success() {
+    let (context, tx) = create_counted_cells_context(2);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_aggregate_payment_counted_run_counted_error() {
+    // the run of the counted order also pays the aggregate order
+    let (context, tx) = create_counted_cells_context(0);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
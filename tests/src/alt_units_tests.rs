@@ -0,0 +1,290 @@
+use dex_errors::DexLockError;
+use dex_types::{DexArgsBuilder, BASIS_POINTS};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const ROYALTY_RATE: u16 = 250;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Payment {
+    // the unit of the order
+    Ckb,
+    // the alternative unit
+    Udt,
+    // a UDT which is not accepted by the order
+    OtherUdt,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Underpaid,
+    // the royalty is paid in CKB while the seller is paid in the UDT
+    RoyaltyUnitNotMatch,
+}
+
+fn create_test_context(payment: Payment, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![42]))
+        .expect("script");
+    let other_sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![43]))
+        .expect("script");
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let royalty_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut royalty_lock_hash = [0u8; 32];
+    royalty_lock_hash.copy_from_slice(royalty_lock.calc_script_hash().as_slice());
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(sudt_type_script.calc_script_hash().as_slice());
+
+    // an NFT priced at 1000 CKB or 50 of the UDT
+    let ckb_value = 1000_0000_0000u128;
+    let udt_value = 50_0000_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), ckb_value)
+        .nft()
+        .alt_unit(Some(unit_type_hash), udt_value)
+        .royalty(royalty_lock_hash, ROYALTY_RATE)
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        Bytes::new(),
+    );
+
+    let buyer_unit = match payment {
+        Payment::OtherUdt => other_sudt_type_script,
+        _ => sudt_type_script,
+    };
+    let buyer_token = 4000_0000_0000u128;
+    let buyer_ckb_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(5000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let buyer_udt_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(buyer_unit.clone()).pack())
+            .build(),
+        Bytes::from(buyer_token.to_le_bytes().to_vec()),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_ckb_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_udt_out_point)
+            .build(),
+    ];
+
+    let paid_value = if payment == Payment::Ckb {
+        ckb_value
+    } else {
+        udt_value
+    };
+    let royalty_value = paid_value * ROYALTY_RATE as u128 / BASIS_POINTS as u128;
+    let seller_value = paid_value - royalty_value;
+    let seller_value = if error == DexError::Underpaid {
+        seller_value - 1
+    } else {
+        seller_value
+    };
+    let ckb_payment = |lock: Script, value: u128| {
+        (
+            CellOutput::new_builder()
+                .capacity((value as u64).pack())
+                .lock(lock)
+                .build(),
+            Bytes::new(),
+        )
+    };
+    let udt_payment = |lock: Script, value: u128| {
+        (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(lock)
+                .type_(Some(buyer_unit.clone()).pack())
+                .build(),
+            Bytes::from(value.to_le_bytes().to_vec()),
+        )
+    };
+
+    let mut payments = vec![];
+    let mut udt_change = buyer_token;
+    if payment == Payment::Ckb {
+        payments.push(ckb_payment(owner_lock, seller_value));
+        payments.push(ckb_payment(royalty_lock, royalty_value));
+    } else {
+        payments.push(udt_payment(owner_lock, seller_value));
+        udt_change -= seller_value;
+        if error == DexError::RoyaltyUnitNotMatch {
+            payments.push(ckb_payment(royalty_lock, royalty_value));
+        } else {
+            payments.push(udt_payment(royalty_lock, royalty_value));
+            udt_change -= royalty_value;
+        }
+    }
+
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    for (output, data) in payments {
+        outputs.push(output);
+        outputs_data.push(data);
+    }
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+    );
+    outputs_data.push(Bytes::new());
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock)
+            .type_(Some(buyer_unit).pack())
+            .build(),
+    );
+    outputs_data.push(Bytes::from(udt_change.to_le_bytes().to_vec()));
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_alt_units_paid_in_ckb_success() {
+    let (context, tx) = create_test_context(Payment::Ckb, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_alt_units_paid_in_udt_success() {
+    let (context, tx) = create_test_context(Payment::Udt, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_alt_units_ckb_underpaid_error() {
+    let (context, tx) = create_test_context(Payment::Ckb, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_alt_units_udt_underpaid_error() {
+    let (context, tx) = create_test_context(Payment::Udt, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_alt_units_unit_not_accepted_error() {
+    let (context, tx) = create_test_context(Payment::OtherUdt, DexError::NoError);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::UnitTypeNotMatch);
+}
+
+#[test]
+fn test_dex_alt_units_royalty_unit_not_match_error() {
+    let (context, tx) = create_test_context(Payment::Udt, DexError::RoyaltyUnitNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::UnitTypeNotMatch);
+}
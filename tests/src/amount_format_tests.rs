@@ -0,0 +1,244 @@
+use dex_errors::DexLockError;
+use dex_types::{AmountFormat, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Underpaid,
+    // a cell of the buyer splits the run of counted cells
+    RunInterrupted,
+    // a second order of the owner is at the index after the first one, whose payment cannot take
+    // the counted cells of the second one
+    RunSharedWithOrder,
+}
+
+fn create_test_context(format: AmountFormat, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    // An always success type script plays the unit, whose data is not checked
+    let unit_type = context
+        .build_script(&always_success_out_point, Bytes::from(vec![42]))
+        .expect("script");
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(unit_type.calc_script_hash().as_slice());
+
+    // an NFT priced at 1234 tokens or at 2 counted cells
+    let total_value = match format {
+        AmountFormat::CellCount => 2u128,
+        _ => 1234_0000_0000u128,
+    };
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .nft()
+        .unit_type_hash(unit_type_hash)
+        .amount_format(format)
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let orders = if error == DexError::RunSharedWithOrder {
+        2
+    } else {
+        1
+    };
+    let asset_types: Vec<Script> = (0..orders)
+        .map(|order| {
+            ScriptBuilder::default()
+                .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+                .hash_type(Byte::from(2u8))
+                .args(Bytes::from(vec![order]).pack())
+                .build()
+        })
+        .collect();
+
+    let dex_capacity = 300_0000_0000u64;
+    let mut inputs = vec![];
+    for asset_type in &asset_types {
+        let dex_input_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(dex_lock_script.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            Bytes::new(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(dex_input_out_point)
+                .build(),
+        );
+    }
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(5000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    inputs.push(
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    );
+
+    let unit_output = |lock: &Script| {
+        CellOutput::new_builder()
+            .capacity(200_0000_0000u64.pack())
+            .lock(lock.clone())
+            .type_(Some(unit_type.clone()).pack())
+            .build()
+    };
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    match format {
+        AmountFormat::CellCount => {
+            let cells = match error {
+                DexError::Underpaid => 1,
+                DexError::RunSharedWithOrder => 4,
+                _ => 2,
+            };
+            for cell in 0..cells {
+                if cell == 1 && error == DexError::RunInterrupted {
+                    outputs.push(unit_output(&buyer_lock));
+                    outputs_data.push(Bytes::new());
+                }
+                outputs.push(unit_output(&owner_lock));
+                outputs_data.push(Bytes::new());
+            }
+        }
+        _ => {
+            let paid_value = if error == DexError::Underpaid {
+                total_value - 1
+            } else {
+                total_value
+            };
+            let data = match format {
+                AmountFormat::UdtBigEndian => paid_value.to_be_bytes(),
+                _ => paid_value.to_le_bytes(),
+            };
+            outputs.push(unit_output(&owner_lock));
+            outputs_data.push(Bytes::from(data.to_vec()));
+        }
+    }
+    for asset_type in asset_types {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(asset_type).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::new());
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_amount_format_big_endian_success() {
+    let (context, tx) = create_test_context(AmountFormat::UdtBigEndian, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_amount_format_big_endian_underpaid_error() {
+    let (context, tx) = create_test_context(AmountFormat::UdtBigEndian, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_amount_format_cell_count_success() {
+    let (context, tx) = create_test_context(AmountFormat::CellCount, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_amount_format_cell_count_underpaid_error() {
+    let (context, tx) = create_test_context(AmountFormat::CellCount, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_amount_format_cell_count_run_interrupted_error() {
+    let (context, tx) = create_test_context(AmountFormat::CellCount, DexError::RunInterrupted);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_amount_format_cell_count_run_shared_with_order_error() {
+    let (context, tx) = create_test_context(AmountFormat::CellCount, DexError::RunSharedWithOrder);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
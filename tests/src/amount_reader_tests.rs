@@ -0,0 +1,192 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// the data2 hash type of the reader, matched by the data hash of the cell dep
+const READER_HASH_TYPE: u8 = 4;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Underpaid,
+    // the reader is not in the cell deps
+    ReaderMissing,
+    // the payment data is shorter than the amount, and the reader exits with an error
+    ReaderFailed,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+    let reader_bin: Bytes = Loader::default().load_binary("udt-amount-reader");
+    let reader_code_hash = blake2b_256(&reader_bin);
+    let reader_out_point = context.deploy_cell(reader_bin);
+    let reader_dep = CellDep::new_builder().out_point(reader_out_point).build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    // An always success type script plays the unit, whose data is not checked
+    let unit_type = context
+        .build_script(&always_success_out_point, Bytes::from(vec![42]))
+        .expect("script");
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(unit_type.calc_script_hash().as_slice());
+
+    // an NFT priced at 1234 tokens read by the reader
+    let total_value = 1234_0000_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .nft()
+        .unit_type_hash(unit_type_hash)
+        .amount_reader(reader_code_hash, READER_HASH_TYPE)
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .args(Bytes::from(vec![0]).pack())
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        Bytes::new(),
+    );
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(5000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let paid_value = if error == DexError::Underpaid {
+        total_value - 1
+    } else {
+        total_value
+    };
+    let payment_data = if error == DexError::ReaderFailed {
+        Bytes::from(paid_value.to_le_bytes()[..8].to_vec())
+    } else {
+        Bytes::from(paid_value.to_le_bytes().to_vec())
+    };
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(200_0000_0000u64.pack())
+            .lock(owner_lock)
+            .type_(Some(unit_type).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock)
+            .type_(Some(asset_type).pack())
+            .build(),
+    ];
+    let outputs_data = vec![payment_data, Bytes::new()];
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let mut cell_deps = vec![always_success_dep, dex_dep];
+    if error != DexError::ReaderMissing {
+        cell_deps.push(reader_dep);
+    }
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_amount_reader_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_amount_reader_underpaid_error() {
+    let (context, tx) = create_test_context(DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_amount_reader_missing_error() {
+    let (context, tx) = create_test_context(DexError::ReaderMissing);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAmountReaderMissing);
+}
+
+#[test]
+fn test_dex_amount_reader_failed_error() {
+    let (context, tx) = create_test_context(DexError::ReaderFailed);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAmountReaderFailed);
+}
@@ -0,0 +1,539 @@
+use ckb_testtool::ckb_types::{bytes::Bytes, core::ScriptHashType, packed::*, prelude::*};
+use dex_types::{extension, setup, since, AmountFormat, ArgsError, DexArgs, DexArgsBuilder};
+
+fn owner_lock() -> Script {
+    Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(vec![2u8; 20]).pack())
+        .build()
+}
+
+#[test]
+fn test_dex_args_builder_round_trip() {
+    let owner_lock = owner_lock();
+    let builders = vec![
+        DexArgsBuilder::new(owner_lock.as_slice(), 1234_5678_0000),
+        DexArgsBuilder::new(owner_lock.as_slice(), 1).receiver_lock([3u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), u128::MAX).unit_type_hash([4u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .receiver_lock([3u8; 32])
+            .unit_type_hash([4u8; 32])
+            .nft(),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .partial_fill(),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .receiver_lock([3u8; 32])
+            .expiry(since::absolute_timestamp(1_700_000_000)),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .expiry(since::absolute_block_number(100))
+            .scoped_cancel()
+            .nft_type_hash([5u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .royalty([6u8; 32], 250)
+            .platform_fee([7u8; 32], 9750)
+            .taker_lock_hash([8u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .bid([9u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .dutch_auction(
+                21,
+                since::absolute_timestamp(1_700_000_000),
+                since::absolute_timestamp(1_700_086_400),
+            ),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .scoped_cancel()
+            .english_auction(since::absolute_block_number(100)),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .royalty([6u8; 32], 250)
+            .bundle([10u8; 32], 3),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .alt_unit(Some([4u8; 32]), 21)
+            .alt_unit(Some([11u8; 32]), 7),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .alt_unit(None, 21),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .asset(Some([12u8; 32]), [13u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42).asset(None, [13u8; 32]),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .royalty([6u8; 32], 250)
+            .amount_format(AmountFormat::UdtBigEndian),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .nft()
+            .unit_type_hash([4u8; 32])
+            .amount_format(AmountFormat::CellCount),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .scoped_cancel()
+            .aggregate_payment(),
+        DexArgsBuilder::new(owner_lock.as_slice(), 42)
+            .unit_type_hash([4u8; 32])
+            .royalty([6u8; 32], 250)
+            .amount_reader([14u8; 32], 1),
+    ];
+    for builder in builders {
+        let args = builder.build().unwrap();
+        let data = args.to_vec().unwrap();
+        assert_eq!(DexArgs::from_slice(&data).unwrap(), args);
+    }
+}
+
+#[test]
+fn test_dex_args_builder_setup_flags() {
+    let args = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .receiver_lock([3u8; 32])
+        .unit_type_hash([4u8; 32])
+        .nft()
+        .build()
+        .unwrap();
+    assert_eq!(
+        args.setup,
+        setup::RECEIVER_LOCK | setup::UNIT_TYPE_HASH | setup::NFT
+    );
+    assert!(args.is_nft());
+    assert!(!args.is_partial_fill());
+    assert_eq!(args.version(), setup::ARGS_VERSION_0);
+}
+
+#[test]
+fn test_dex_args_builder_nft_partial_fill_error() {
+    let result = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .nft()
+        .partial_fill()
+        .build();
+    assert_eq!(result.unwrap_err(), ArgsError::DexSetupInvalid);
+}
+
+#[test]
+fn test_dex_args_builder_version_unknown_error() {
+    let result = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .version(15)
+        .build();
+    assert_eq!(result.unwrap_err(), ArgsError::DexVersionUnknown);
+}
+
+#[test]
+fn test_dex_args_missing_hash_error() {
+    let args = DexArgs {
+        owner_lock:     owner_lock().as_slice().to_vec(),
+        setup:          setup::UNIT_TYPE_HASH,
+        total_value:    42,
+        receiver_lock:  None,
+        unit_type_hash: None,
+        extension:      None,
+    };
+    assert_eq!(args.to_vec().unwrap_err(), ArgsError::LockArgsInvalid);
+
+    let data = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .build()
+        .unwrap()
+        .to_vec()
+        .unwrap();
+    let mut data_with_flag = data.clone();
+    data_with_flag[owner_lock().as_slice().len()] |= setup::RECEIVER_LOCK;
+    data_with_flag.extend([0u8; 16]);
+    assert_eq!(
+        DexArgs::from_slice(&data_with_flag).unwrap_err(),
+        ArgsError::LockArgsInvalid
+    );
+}
+
+#[test]
+fn test_dex_args_owner_lock_encoding_error() {
+    let mut data = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .build()
+        .unwrap()
+        .to_vec()
+        .unwrap();
+    // break the offset of the hash_type field of the owner lock
+    data[8] = 0xff;
+    assert_eq!(DexArgs::from_slice(&data).unwrap_err(), ArgsError::Encoding);
+}
+
+#[test]
+fn test_dex_args_builder_expiry() {
+    let expiry = since::absolute_block_number(1_000_000);
+    let args = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .expiry(expiry)
+        .build()
+        .unwrap();
+    assert_eq!(args.version(), setup::ARGS_VERSION_1);
+    assert_eq!(args.expiry(), Some(expiry));
+    // the extension follows the fields of version 0
+    let data = args.to_vec().unwrap();
+    assert_eq!(data.len(), args.extension_pos() + 2 + 8);
+}
+
+#[test]
+fn test_dex_args_builder_expiry_invalid_error() {
+    let relative_expiry = since::RELATIVE_FLAG | 100;
+    let epoch_expiry = since::METRIC_EPOCH | 100;
+    for expiry in [relative_expiry, epoch_expiry] {
+        let result = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+            .expiry(expiry)
+            .build();
+        assert_eq!(result.unwrap_err(), ArgsError::DexExpiryInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_malformed_error() {
+    let owner_size = owner_lock().as_slice().len();
+    let v0 = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .receiver_lock([3u8; 32])
+        .build()
+        .unwrap()
+        .to_vec()
+        .unwrap();
+    let v1 = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .expiry(since::absolute_block_number(100))
+        .build()
+        .unwrap()
+        .to_vec()
+        .unwrap();
+    let extension_pos = owner_size + 17;
+
+    let with = |data: &[u8], f: &dyn Fn(&mut Vec<u8>)| {
+        let mut data = data.to_vec();
+        f(&mut data);
+        data
+    };
+    let cases: Vec<(&str, Vec<u8>, ArgsError)> = vec![
+        ("too short", v0[..60].to_vec(), ArgsError::LockArgsInvalid),
+        (
+            "v0 trailing byte",
+            with(&v0, &|data| data.push(0)),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "v0 trailing hash",
+            with(&v0, &|data| data.extend([4u8; 32])),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "v0 truncated hash",
+            with(&v0, &|data| data.truncate(data.len() - 1)),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "owner header larger than the script",
+            with(&v0, &|data| {
+                data[0] += 1;
+                data.insert(owner_size, 0);
+            }),
+            ArgsError::Encoding,
+        ),
+        (
+            "owner header smaller than the script",
+            with(&v0, &|data| data[0] -= 1),
+            ArgsError::Encoding,
+        ),
+        (
+            "v1 trailing byte",
+            with(&v1, &|data| data.push(0)),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "v1 missing extension",
+            with(&v1, &|data| data.truncate(extension_pos)),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "v1 truncated expiry",
+            with(&v1, &|data| data.truncate(data.len() - 1)),
+            ArgsError::LockArgsInvalid,
+        ),
+        (
+            "v1 unknown extension flag",
+            with(&v1, &|data| data[extension_pos] |= 0b1000_0000),
+            ArgsError::DexSetupInvalid,
+        ),
+        (
+            "v1 flag without field",
+            with(&v1, &|data| {
+                data[extension_pos + 1] = extension::SCOPED_CANCEL as u8;
+            }),
+            ArgsError::LockArgsInvalid,
+        ),
+    ];
+    for (name, data, err) in cases {
+        assert_eq!(DexArgs::from_slice(&data).unwrap_err(), err, "{}", name);
+    }
+}
+
+#[test]
+fn test_dex_args_nft_type_hash_setup_invalid_error() {
+    let args = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .nft_type_hash([5u8; 32])
+        .build()
+        .unwrap();
+    assert!(args.is_nft());
+    assert_eq!(args.nft_type_hash(), Some([5u8; 32]));
+
+    // only NFT orders record the type hash of the NFT
+    let mut data = args.to_vec().unwrap();
+    data[owner_lock().as_slice().len()] &= !setup::NFT;
+    assert_eq!(
+        DexArgs::from_slice(&data).unwrap_err(),
+        ArgsError::DexSetupInvalid
+    );
+}
+
+#[test]
+fn test_dex_args_share_rate_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42);
+    let cases = vec![
+        builder.clone().royalty([6u8; 32], 0),
+        builder.clone().platform_fee([7u8; 32], 10_001),
+        // the shares together cannot exceed the paid value
+        builder
+            .clone()
+            .royalty([6u8; 32], 5000)
+            .platform_fee([7u8; 32], 5001),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexRateInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_bid_setup_invalid_error() {
+    let bid = DexArgsBuilder::new(owner_lock().as_slice(), 42).bid([9u8; 32]);
+    let cases = vec![
+        bid.clone().partial_fill(),
+        bid.clone().nft_type_hash([5u8; 32]),
+        bid.clone().royalty([6u8; 32], 250),
+        bid.platform_fee([7u8; 32], 250),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_dutch_auction_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42);
+    let start = since::absolute_block_number(100);
+    let end = since::absolute_block_number(200);
+    let cases = vec![
+        (
+            builder.clone().dutch_auction(42, start, end),
+            ArgsError::DexAuctionInvalid,
+        ),
+        (
+            builder.clone().dutch_auction(21, end, start),
+            ArgsError::DexAuctionInvalid,
+        ),
+        (
+            builder
+                .clone()
+                .dutch_auction(21, start, since::absolute_timestamp(200)),
+            ArgsError::DexAuctionInvalid,
+        ),
+        (
+            builder.clone().dutch_auction(
+                21,
+                since::RELATIVE_FLAG | 100,
+                since::RELATIVE_FLAG | 200,
+            ),
+            ArgsError::DexAuctionInvalid,
+        ),
+        (
+            builder.clone().partial_fill().dutch_auction(21, start, end),
+            ArgsError::DexSetupInvalid,
+        ),
+        (
+            builder.bid([9u8; 32]).dutch_auction(21, start, end),
+            ArgsError::DexSetupInvalid,
+        ),
+    ];
+    for (builder, err) in cases {
+        assert_eq!(builder.build().unwrap_err(), err);
+    }
+}
+
+#[test]
+fn test_dex_args_english_auction_setup_invalid_error() {
+    let auction = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .english_auction(since::absolute_block_number(100));
+    let cases = vec![
+        auction.clone().unit_type_hash([4u8; 32]),
+        auction.clone().partial_fill(),
+        auction.clone().royalty([6u8; 32], 250),
+        auction.clone().taker_lock_hash([8u8; 32]),
+        auction.expiry(since::absolute_block_number(100)),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+    let result = DexArgsBuilder::new(owner_lock().as_slice(), 42)
+        .english_auction(since::RELATIVE_FLAG | 100)
+        .build();
+    assert_eq!(result.unwrap_err(), ArgsError::DexAuctionInvalid);
+}
+
+#[test]
+fn test_dex_args_bundle_setup_invalid_error() {
+    let bundle = DexArgsBuilder::new(owner_lock().as_slice(), 42).bundle([10u8; 32], 3);
+    let cases = vec![
+        (
+            DexArgsBuilder::new(owner_lock().as_slice(), 42).bundle([10u8; 32], 1),
+            ArgsError::DexSetupInvalid,
+        ),
+        (
+            bundle.clone().unit_type_hash([4u8; 32]).partial_fill(),
+            ArgsError::DexSetupInvalid,
+        ),
+        (
+            bundle.clone().expiry(since::absolute_block_number(100)),
+            ArgsError::DexSetupInvalid,
+        ),
+        (bundle.clone().scoped_cancel(), ArgsError::DexSetupInvalid),
+        (
+            bundle.clone().nft().nft_type_hash([5u8; 32]),
+            ArgsError::DexSetupInvalid,
+        ),
+        (bundle.clone().bid([9u8; 32]), ArgsError::DexSetupInvalid),
+        (
+            bundle.english_auction(since::absolute_block_number(100)),
+            ArgsError::DexSetupInvalid,
+        ),
+    ];
+    for (builder, err) in cases {
+        assert_eq!(builder.build().unwrap_err(), err);
+    }
+}
+
+#[test]
+fn test_dex_args_alt_units_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42).unit_type_hash([4u8; 32]);
+    let cases = vec![
+        builder.clone().alt_unit(Some([4u8; 32]), 21),
+        builder
+            .clone()
+            .alt_unit(None, 21)
+            .alt_unit(Some([11u8; 32]), 7)
+            .alt_unit(None, 7),
+        builder
+            .clone()
+            .alt_unit(None, 21)
+            .alt_unit(Some([11u8; 32]), 7)
+            .alt_unit(Some([12u8; 32]), 7)
+            .alt_unit(Some([13u8; 32]), 7),
+        builder.clone().alt_unit(Some([0u8; 32]), 21),
+        builder.clone().partial_fill().alt_unit(None, 21),
+        builder.bid([9u8; 32]).alt_unit(None, 21),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_asset_setup_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42).asset(None, [13u8; 32]);
+    let cases = vec![
+        builder.clone().unit_type_hash([4u8; 32]).partial_fill(),
+        builder.bundle([10u8; 32], 3),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_amount_format_setup_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42);
+    let cases = vec![
+        builder.clone().amount_format(AmountFormat::UdtBigEndian),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .alt_unit(None, 21)
+            .amount_format(AmountFormat::UdtBigEndian),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .bid([9u8; 32])
+            .amount_format(AmountFormat::UdtBigEndian),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .platform_fee([7u8; 32], 100)
+            .amount_format(AmountFormat::CellCount),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+
+    // the format is the last field, and an unknown one is rejected
+    let mut data = builder
+        .unit_type_hash([4u8; 32])
+        .amount_format(AmountFormat::CellCount)
+        .build()
+        .unwrap()
+        .to_vec()
+        .unwrap();
+    *data.last_mut().unwrap() = 3;
+    assert_eq!(
+        DexArgs::from_slice(&data).unwrap_err(),
+        ArgsError::DexSetupInvalid
+    );
+}
+
+#[test]
+fn test_dex_args_amount_reader_setup_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42);
+    let cases = vec![
+        builder.clone().amount_reader([14u8; 32], 1),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .amount_format(AmountFormat::UdtBigEndian)
+            .amount_reader([14u8; 32], 1),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .alt_unit(None, 21)
+            .amount_reader([14u8; 32], 1),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .bid([9u8; 32])
+            .amount_reader([14u8; 32], 1),
+        builder
+            .clone()
+            .unit_type_hash([4u8; 32])
+            .amount_reader([14u8; 32], 3),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+}
+
+#[test]
+fn test_dex_args_aggregate_payment_setup_invalid_error() {
+    let builder = DexArgsBuilder::new(owner_lock().as_slice(), 42).aggregate_payment();
+    let cases = vec![
+        builder.clone().unit_type_hash([4u8; 32]).partial_fill(),
+        builder.clone().nft_type_hash([5u8; 32]),
+        builder.clone().royalty([6u8; 32], 250),
+        builder.clone().platform_fee([7u8; 32], 100),
+        builder.clone().bid([9u8; 32]),
+        builder.clone().bundle([10u8; 32], 3),
+        builder.alt_unit(None, 21),
+    ];
+    for builder in cases {
+        assert_eq!(builder.build().unwrap_err(), ArgsError::DexSetupInvalid);
+    }
+}
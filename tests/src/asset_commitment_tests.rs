@@ -0,0 +1,194 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // the DEX cell holds other data than the committed one
+    DataNotMatch,
+    // the DEX cell has another type than the committed one
+    TypeNotMatch,
+    // the owner takes back the cell which does not match its commitment
+    OwnerCancel,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .args(Bytes::from(vec![1u8]).pack())
+        .build();
+    let other_type = asset_type
+        .clone()
+        .as_builder()
+        .args(Bytes::from(vec![2u8]).pack())
+        .build();
+    let asset_data = Bytes::from(vec![7u8; 40]);
+
+    // the order commits to the asset type and data
+    let mut type_hash = [0u8; 32];
+    type_hash.copy_from_slice(asset_type.calc_script_hash().as_slice());
+    let total_value = 1234_5678_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .nft()
+        .asset(Some(type_hash), blake2b_256(&asset_data))
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let (dex_type, dex_data) = match error {
+        DexError::DataNotMatch | DexError::OwnerCancel => {
+            (asset_type.clone(), Bytes::from(vec![8u8; 40]))
+        }
+        DexError::TypeNotMatch => (other_type, asset_data),
+        DexError::NoError => (asset_type, asset_data),
+    };
+    let dex_capacity = 300_0000_0000u64;
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(dex_type.clone()).pack())
+            .build(),
+        dex_data.clone(),
+    );
+    let payer_lock = if error == DexError::OwnerCancel {
+        owner_lock.clone()
+    } else {
+        buyer_lock.clone()
+    };
+    let payer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(5000_0000_0000u64.pack())
+            .lock(payer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(payer_out_point)
+            .build(),
+    ];
+
+    let mut outputs = vec![];
+    if error != DexError::OwnerCancel {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity((total_value as u64).pack())
+                .lock(owner_lock)
+                .build(),
+        );
+    }
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(payer_lock)
+            .type_(Some(dex_type).pack())
+            .build(),
+    );
+    let mut outputs_data = vec![dex_data];
+    if error != DexError::OwnerCancel {
+        outputs_data.insert(0, Bytes::new());
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_asset_commitment_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_asset_commitment_owner_cancel_success() {
+    let (context, tx) = create_test_context(DexError::OwnerCancel);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_asset_commitment_data_not_match_error() {
+    let (context, tx) = create_test_context(DexError::DataNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAssetNotMatch);
+}
+
+#[test]
+fn test_dex_asset_commitment_type_not_match_error() {
+    let (context, tx) = create_test_context(DexError::TypeNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAssetNotMatch);
+}
@@ -0,0 +1,270 @@
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[repr(i8)]
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    DexOwnerLockNotMatch = 6,
+    UnitTypeNotMatch = 11,
+    TotalValueNotMatch,
+    UnitDataLengthNotEnough = 18,
+    // not an error, the delivery is an xUDT cell with extension data after the amount
+    XudtExtensionData = 100,
+    // not an error, the bid is for an NFT and paid in the UDT
+    NftBid,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    // The sUDT script requires exactly 16 bytes of data, so an always success type script plays
+    // the xUDT whose data may be shorter or longer than that, and the NFT
+    let target_type_script = if matches!(
+        error,
+        DexError::UnitDataLengthNotEnough | DexError::XudtExtensionData | DexError::NftBid
+    ) {
+        context
+            .build_script(&always_success_out_point, Bytes::from(vec![42]))
+            .expect("script")
+    } else {
+        context
+            .build_script(&sudt_out_point, Bytes::from(vec![42]))
+            .expect("script")
+    };
+    // the bid of an NFT is paid in another UDT
+    let unit_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![43]))
+        .expect("script");
+
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let seller_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let input_token: u128 = 4000_0000_0000;
+    let total_value: u128 = 1234_5678_0000;
+    let change: u128 = input_token - total_value;
+
+    let mut target_type_hash = [0u8; 32];
+    target_type_hash.copy_from_slice(&target_type_script.calc_script_hash().as_bytes()[0..32]);
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(&unit_type_script.calc_script_hash().as_bytes()[0..32]);
+
+    // the bid of a fungible token holds CKB and the bid of an NFT holds the UDT
+    let (dex_args, bid_output, bid_data) = if error == DexError::NftBid {
+        let args = DexArgsBuilder::new(owner_lock.as_slice(), 0)
+            .unit_type_hash(unit_type_hash)
+            .nft()
+            .bid(target_type_hash)
+            .build()
+            .unwrap();
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .type_(Some(unit_type_script.clone()).pack())
+            .build();
+        (args, output, Bytes::from(500u128.to_le_bytes().to_vec()))
+    } else {
+        let args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+            .bid(target_type_hash)
+            .build()
+            .unwrap();
+        let output = CellOutput::new_builder().capacity(5000u64.pack()).build();
+        (args, output, Bytes::default())
+    };
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let input_out_point = context.create_cell(
+        bid_output
+            .clone()
+            .as_builder()
+            .lock(dex_lock_script)
+            .build(),
+        bid_data.clone(),
+    );
+
+    let input_out_point1 = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(4000u64.pack())
+            .lock(seller_lock.clone())
+            .type_(Some(target_type_script.clone()).pack())
+            .build(),
+        input_token.to_le_bytes().to_vec().into(),
+    );
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(input_out_point1)
+            .build(),
+    ];
+
+    let first_output_lock = if error == DexError::DexOwnerLockNotMatch {
+        seller_lock.clone()
+    } else {
+        owner_lock.clone()
+    };
+    let first_output = if error == DexError::UnitTypeNotMatch {
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(first_output_lock)
+            .build()
+    } else {
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(first_output_lock)
+            .type_(Some(target_type_script.clone()).pack())
+            .build()
+    };
+
+    let mut outputs = vec![
+        first_output,
+        bid_output.as_builder().lock(seller_lock.clone()).build(),
+    ];
+
+    let first_output_data = match error {
+        DexError::TotalValueNotMatch => Bytes::from((total_value - 1).to_le_bytes().to_vec()),
+        DexError::UnitDataLengthNotEnough => Bytes::from(total_value.to_le_bytes()[..8].to_vec()),
+        DexError::XudtExtensionData => {
+            let mut data = total_value.to_le_bytes().to_vec();
+            data.extend([0x55u8; 36]);
+            Bytes::from(data)
+        }
+        // the NFT is delivered as a whole
+        DexError::NftBid => input_token.to_le_bytes().to_vec().into(),
+        _ => Bytes::from(total_value.to_le_bytes().to_vec()),
+    };
+
+    let mut outputs_data = vec![first_output_data, bid_data];
+    if error != DexError::NftBid {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .type_(Some(target_type_script.clone()).pack())
+                .lock(seller_lock)
+                .build(),
+        );
+        outputs_data.push(Bytes::from(change.to_le_bytes().to_vec()));
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_bid_order_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_bid_nft_order_success() {
+    let (context, tx) = create_test_context(DexError::NftBid);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_bid_owner_lock_not_match_error() {
+    let (context, tx) = create_test_context(DexError::DexOwnerLockNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexError::DexOwnerLockNotMatch as i8);
+}
+
+#[test]
+fn test_dex_bid_unit_type_not_match_error() {
+    let (context, tx) = create_test_context(DexError::UnitTypeNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexError::UnitTypeNotMatch as i8);
+}
+
+#[test]
+fn test_dex_bid_total_value_not_match_error() {
+    let (context, tx) = create_test_context(DexError::TotalValueNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexError::TotalValueNotMatch as i8);
+}
+
+#[test]
+fn test_dex_bid_xudt_extension_data_success() {
+    let (context, tx) = create_test_context(DexError::XudtExtensionData);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_bid_unit_data_length_not_enough_error() {
+    let (context, tx) = create_test_context(DexError::UnitDataLengthNotEnough);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexError::UnitDataLengthNotEnough as i8);
+}
@@ -0,0 +1,281 @@
+use dex_errors::DexLockError;
+use dex_types::{DexArgsBuilder, BASIS_POINTS};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const BUNDLE_MEMBERS: u8 = 3;
+const ROYALTY_RATE: u16 = 500;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Asset {
+    // every member is an NFT
+    Nft,
+    // every member is a fungible token cell, whose capacity is paid back with the bundle
+    Ft,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // only two of the three members are spent
+    MemberMissing,
+    Underpaid,
+    // the royalty output is the payment of an order which is not a member
+    OutputIndexConflict,
+}
+
+fn create_test_context(asset: Asset, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let royalty_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut royalty_lock_hash = [0u8; 32];
+    royalty_lock_hash.copy_from_slice(royalty_lock.calc_script_hash().as_slice());
+
+    // the price of the whole bundle
+    let total_value = 900_0000_0000u128;
+    let royalty_value = total_value * ROYALTY_RATE as u128 / BASIS_POINTS as u128;
+    let seller_value = total_value - royalty_value;
+
+    let builder = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .bundle(rng.gen::<[u8; 32]>(), BUNDLE_MEMBERS)
+        .royalty(royalty_lock_hash, ROYALTY_RATE);
+    let dex_args = match asset {
+        Asset::Nft => builder.nft(),
+        Asset::Ft => builder,
+    }
+    .build()
+    .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    // every member holds a different asset
+    let asset_types: Vec<Script> = (0..BUNDLE_MEMBERS)
+        .map(|member| {
+            ScriptBuilder::default()
+                .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+                .hash_type(Byte::from(2u8))
+                .args(Bytes::from(vec![member]).pack())
+                .build()
+        })
+        .collect();
+    let asset_data = match asset {
+        Asset::Nft => Bytes::new(),
+        Asset::Ft => Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    };
+
+    let dex_capacity = 200_0000_0000u64;
+    let spent_members = if error == DexError::MemberMissing {
+        BUNDLE_MEMBERS as usize - 1
+    } else {
+        BUNDLE_MEMBERS as usize
+    };
+    let mut inputs = vec![];
+    for asset_type in asset_types.iter().take(spent_members) {
+        let member_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(dex_lock_script.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            asset_data.clone(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(member_out_point)
+                .build(),
+        );
+    }
+    if error == DexError::OutputIndexConflict {
+        // an NFT order of the royalty receiver at the index of the royalty output
+        let conflict_args = DexArgsBuilder::new(royalty_lock.as_slice(), royalty_value)
+            .nft()
+            .build()
+            .unwrap();
+        let conflict_lock_script = context
+            .build_script(&dex_out_point, conflict_args.to_vec().unwrap().into())
+            .expect("script");
+        let conflict_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(conflict_lock_script)
+                .type_(Some(asset_types[0].clone()).pack())
+                .build(),
+            Bytes::new(),
+        );
+        inputs.insert(
+            1,
+            CellInput::new_builder()
+                .previous_output(conflict_out_point)
+                .build(),
+        );
+    }
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(5000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    inputs.push(
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    );
+
+    // one payment settles the bundle, and a fungible token bundle also pays back the capacity of
+    // every member
+    let seller_value = match asset {
+        Asset::Nft => seller_value,
+        Asset::Ft => seller_value + dex_capacity as u128 * spent_members as u128,
+    };
+    let seller_value = if error == DexError::Underpaid {
+        seller_value - 1
+    } else {
+        seller_value
+    };
+    let mut outputs = vec![
+        CellOutput::new_builder()
+            .capacity((seller_value as u64).pack())
+            .lock(owner_lock)
+            .build(),
+        CellOutput::new_builder()
+            .capacity((royalty_value as u64).pack())
+            .lock(royalty_lock)
+            .build(),
+    ];
+    let mut outputs_data = vec![Bytes::new(), Bytes::new()];
+    if error == DexError::OutputIndexConflict {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(asset_types[0].clone()).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::new());
+    }
+    for asset_type in asset_types.iter().take(spent_members) {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+        );
+        outputs_data.push(asset_data.clone());
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_bundle_nft_success() {
+    let (context, tx) = create_test_context(Asset::Nft, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_bundle_ft_success() {
+    let (context, tx) = create_test_context(Asset::Ft, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_bundle_member_missing_error() {
+    let (context, tx) = create_test_context(Asset::Nft, DexError::MemberMissing);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexBundleIncomplete);
+}
+
+#[test]
+fn test_dex_bundle_nft_underpaid_error() {
+    let (context, tx) = create_test_context(Asset::Nft, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_bundle_ft_underpaid_error() {
+    let (context, tx) = create_test_context(Asset::Ft, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_bundle_output_index_conflict_error() {
+    let (context, tx) = create_test_context(Asset::Nft, DexError::OutputIndexConflict);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexOutputIndexConflict);
+}
@@ -1,4 +1,9 @@
-use self::helper::DexArgs;
+// The cell data is built with `Bytes::try_from`, which newer clippy flags because the
+// conversion from a `Vec<u8>` cannot fail
+#![allow(clippy::unnecessary_fallible_conversions)]
+
+use dex_errors::DexLockError;
+use dex_types::DexArgs;
 
 use super::*;
 use ckb_testtool::builtin::ALWAYS_SUCCESS;
@@ -14,15 +19,15 @@ use rand::{thread_rng, Rng};
 
 const MAX_CYCLES: u64 = 70_000_000;
 
-// error numbers
-const LOCK_ARGS_INVALID: i8 = 5;
-const DEX_FT_TOTAL_VALUE_NOT_MATCH: i8 = 7;
-
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum DexError {
     NoError,
     LockArgsInvalid,
     DexFTTotalValueNotMatch,
+    // the args of the second order have a trailing byte, which the owner can still cancel
+    TrailingArgsBytes,
+    // the args of the second order have a trailing byte and an unknown version
+    DexVersionUnknown,
 }
 
 fn create_test_context(error: DexError) -> (Context, TransactionView) {
@@ -49,27 +54,36 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
         .expect("script");
 
     let dex_args1 = DexArgs {
-        owner_lock:     owner_lock.clone(),
+        owner_lock:     owner_lock.as_slice().to_vec(),
         setup:          0u8,
         total_value:    1234_5678_0000u128,
         receiver_lock:  None,
         unit_type_hash: None,
+        extension:      None,
     };
     let dex_lock_script1 = context
         .build_script(&dex_out_point, dex_args1.to_vec().unwrap().into())
         .expect("script");
 
     let dex_args2 = DexArgs {
-        owner_lock:     owner_lock.clone(),
+        owner_lock:     owner_lock.as_slice().to_vec(),
         setup:          0u8,
         total_value:    9_8765_0000_1234u128,
         receiver_lock:  None,
         unit_type_hash: None,
+        extension:      None,
     };
     let mut dex_args2_vec = dex_args2.to_vec().unwrap();
     if error == DexError::LockArgsInvalid {
         dex_args2_vec.reverse();
     }
+    if error == DexError::TrailingArgsBytes {
+        dex_args2_vec.push(0);
+    }
+    if error == DexError::DexVersionUnknown {
+        dex_args2_vec[owner_lock.as_slice().len()] = 0x20;
+        dex_args2_vec.push(0);
+    }
     let dex_lock_script2 = context
         .build_script(&dex_out_point, dex_args2_vec.into())
         .expect("script");
@@ -171,12 +185,31 @@ fn test_dex_cancel_order_success() {
     println!("consume cycles: {}", cycles);
 }
 
+#[test]
+fn test_dex_cancel_order_trailing_args_bytes_success() {
+    let (context, tx) = create_test_context(DexError::TrailingArgsBytes);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
 #[test]
 fn test_dex_cancel_order_lock_args_error() {
     let (context, tx) = create_test_context(DexError::LockArgsInvalid);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, LOCK_ARGS_INVALID);
+    assert_script_error(err, DexLockError::LockArgsInvalid);
+}
+
+#[test]
+fn test_dex_cancel_order_version_unknown_error() {
+    // only the legacy args of version 0 with trailing bytes can be unlocked by the owner
+    let (context, tx) = create_test_context(DexError::DexVersionUnknown);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexVersionUnknown);
 }
 
 #[test]
@@ -184,5 +217,5 @@ fn test_dex_cancel_order_owner_lock_not_match_error() {
     let (context, tx) = create_test_context(DexError::DexFTTotalValueNotMatch);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, DEX_FT_TOTAL_VALUE_NOT_MATCH);
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
 }
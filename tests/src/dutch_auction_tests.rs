@@ -0,0 +1,211 @@
+use dex_errors::DexLockError;
+use dex_types::{since, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const START_VALUE: u128 = 1000_0000_0000;
+const END_VALUE: u128 = 200_0000_0000;
+const START_BLOCK_NUMBER: u64 = 1000;
+const END_BLOCK_NUMBER: u64 = 2000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Underpaid,
+    // the end value is not lower than the start value
+    AuctionInvalid,
+}
+
+// The since of the DEX input and the price it proves
+fn create_test_context(
+    input_since: u64,
+    price: u128,
+    error: DexError,
+) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), START_VALUE)
+        .nft()
+        .dutch_auction(
+            END_VALUE,
+            since::absolute_block_number(START_BLOCK_NUMBER),
+            since::absolute_block_number(END_BLOCK_NUMBER),
+        )
+        .build()
+        .unwrap();
+    let mut dex_args_vec = dex_args.to_vec().unwrap();
+    if error == DexError::AuctionInvalid {
+        // the encoder refuses an end value above the start value, so set it by hand
+        let end_value_pos = dex_args.extension_pos() + 2;
+        dex_args_vec[end_value_pos..end_value_pos + 16]
+            .copy_from_slice(&(START_VALUE + 1).to_be_bytes());
+    }
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args_vec.into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 200_0000_0000u64;
+    let nft_data = Bytes::from(vec![7u8; 40]);
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        nft_data.clone(),
+    );
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .since(input_since.pack())
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let paid_value = if error == DexError::Underpaid {
+        price - 1
+    } else {
+        price
+    };
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity((paid_value as u64).pack())
+            .lock(owner_lock)
+            .build(),
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock)
+            .type_(Some(asset_type).pack())
+            .build(),
+    ];
+    let outputs_data = vec![Bytes::new(), nft_data];
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_dutch_auction_price_success() {
+    let cases = [
+        // without a since the price is the start value
+        (0, START_VALUE),
+        (
+            since::absolute_block_number(START_BLOCK_NUMBER),
+            START_VALUE,
+        ),
+        // the price decays linearly and is rounded up
+        (since::absolute_block_number(1500), 600_0000_0000),
+        (since::absolute_block_number(1999), 200_8000_0000),
+        (since::absolute_block_number(END_BLOCK_NUMBER), END_VALUE),
+        (since::absolute_block_number(5000), END_VALUE),
+    ];
+    for (input_since, price) in cases {
+        let (context, tx) = create_test_context(input_since, price, DexError::NoError);
+        // run
+        let cycles = context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification");
+        println!("consume cycles: {}", cycles);
+    }
+}
+
+#[test]
+fn test_dex_dutch_auction_underpaid_error() {
+    let (context, tx) = create_test_context(
+        since::absolute_block_number(1500),
+        600_0000_0000,
+        DexError::Underpaid,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_dutch_auction_other_metric_error() {
+    // a timestamp proves nothing about the block number, so the start value must be paid
+    let (context, tx) = create_test_context(
+        since::absolute_timestamp(1500),
+        600_0000_0000,
+        DexError::NoError,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_dutch_auction_invalid_error() {
+    let (context, tx) = create_test_context(0, START_VALUE, DexError::AuctionInvalid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionInvalid);
+}
@@ -0,0 +1,364 @@
+use dex_errors::DexLockError;
+use dex_types::{since, DexArgs, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const RESERVE_PRICE: u128 = 100_0000_0000;
+const TOP_BID: u128 = 150_0000_0000;
+const NEW_BID: u128 = 200_0000_0000;
+const DEADLINE_BLOCK_NUMBER: u64 = 1000;
+const ASSET_CAPACITY: u64 = 1000_0000_0000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Action {
+    // the first bid on an auction without bids
+    FirstBid,
+    // a higher bid replaces the top bid and refunds the top bidder
+    Outbid,
+    // the asset goes to the top bidder and the top bid to the seller after the deadline
+    Settle,
+    // the asset without bids is returned to the owner after the deadline
+    Return,
+    // the owner spends the auction with an owner cell
+    OwnerCancel,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    BidTooLow,
+    EscrowNotEnough,
+    DeadlineChanged,
+    RefundNotMatch,
+    WinnerNotMatch,
+    // the winner receives the asset without the capacity of the auction cell besides the escrow
+    WinnerCapacitySkimmed,
+    SellerUnderpaid,
+    // the settlement is not proven to be after the deadline
+    BeforeDeadline,
+    // the owner cancels an auction with a bid
+    CancelAfterBid,
+    // the auction is listed with the bid of a sock puppet and no escrow
+    FakedBid,
+}
+
+fn create_test_context(action: Action, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let mut random_lock = || {
+        context
+            .build_script(
+                &always_success_out_point,
+                rng.gen::<[u8; 20]>().to_vec().into(),
+            )
+            .expect("script")
+    };
+    let owner_lock = random_lock();
+    let top_bidder_lock = random_lock();
+    let new_bidder_lock = random_lock();
+    let lock_hash = |lock: &Script| {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(lock.calc_script_hash().as_slice());
+        hash
+    };
+
+    let deadline = since::absolute_block_number(DEADLINE_BLOCK_NUMBER);
+    let reserve_args = DexArgsBuilder::new(owner_lock.as_slice(), RESERVE_PRICE)
+        .nft()
+        .english_auction(deadline)
+        .build()
+        .unwrap();
+    // the args after a bid of the bidder
+    let bid_args = |bid: u128, bidder_lock: &Script, deadline: u64| {
+        let mut args: DexArgs = reserve_args.clone();
+        args.total_value = bid;
+        let auction = args
+            .extension
+            .as_mut()
+            .unwrap()
+            .english_auction
+            .as_mut()
+            .unwrap();
+        auction.deadline = deadline;
+        auction.bidder_lock_hash = Some(lock_hash(bidder_lock));
+        args
+    };
+    let dex_lock = |context: &mut Context, args: &DexArgs| {
+        context
+            .build_script(&dex_out_point, args.to_vec().unwrap().into())
+            .expect("script")
+    };
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+    let asset_data = Bytes::from(vec![7u8; 40]);
+    let asset_cell = |lock: Script, capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .type_(Some(asset_type.clone()).pack())
+            .build()
+    };
+    let capacity_cell = |lock: Script, capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .build()
+    };
+
+    // the auction before the transaction, with the top bid escrowed if there is one
+    let (input_args, input_capacity) = match action {
+        Action::FirstBid | Action::Return => (reserve_args.clone(), ASSET_CAPACITY),
+        Action::OwnerCancel if error == DexError::NoError => (reserve_args.clone(), ASSET_CAPACITY),
+        _ => (
+            bid_args(TOP_BID, &top_bidder_lock, deadline),
+            ASSET_CAPACITY + TOP_BID as u64,
+        ),
+    };
+    let input_lock = dex_lock(&mut context, &input_args);
+    // a faked bid leaves only the capacity occupied by the asset in the auction cell
+    let input_capacity = if error == DexError::FakedBid {
+        asset_cell(input_lock.clone(), 0)
+            .occupied_capacity(Capacity::bytes(asset_data.len()).unwrap())
+            .unwrap()
+            .as_u64()
+    } else {
+        input_capacity
+    };
+    let dex_input_out_point =
+        context.create_cell(asset_cell(input_lock, input_capacity), asset_data.clone());
+    let dex_since = match (action, error) {
+        (Action::Settle, DexError::BeforeDeadline) => {
+            since::absolute_block_number(DEADLINE_BLOCK_NUMBER - 1)
+        }
+        (Action::Settle | Action::Return, _) => deadline,
+        _ => 0,
+    };
+    let mut inputs = vec![CellInput::new_builder()
+        .previous_output(dex_input_out_point)
+        .since(dex_since.pack())
+        .build()];
+
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    match action {
+        Action::FirstBid | Action::Outbid => {
+            let bidder_out_point = context.create_cell(
+                capacity_cell(new_bidder_lock.clone(), 5000_0000_0000),
+                Bytes::new(),
+            );
+            inputs.push(
+                CellInput::new_builder()
+                    .previous_output(bidder_out_point)
+                    .build(),
+            );
+            let bid = match (action, error) {
+                (Action::FirstBid, DexError::BidTooLow) => RESERVE_PRICE - 1,
+                (Action::FirstBid, _) => RESERVE_PRICE,
+                (_, DexError::BidTooLow) => TOP_BID,
+                _ => NEW_BID,
+            };
+            let output_deadline = if error == DexError::DeadlineChanged {
+                deadline + 1
+            } else {
+                deadline
+            };
+            let output_lock = dex_lock(
+                &mut context,
+                &bid_args(bid, &new_bidder_lock, output_deadline),
+            );
+            let escrow = if error == DexError::EscrowNotEnough {
+                bid as u64 - 1
+            } else {
+                bid as u64
+            };
+            outputs.push(asset_cell(output_lock, ASSET_CAPACITY + escrow));
+            outputs_data.push(asset_data);
+            if action == Action::Outbid {
+                let refund = if error == DexError::RefundNotMatch {
+                    TOP_BID as u64 - 1
+                } else {
+                    TOP_BID as u64
+                };
+                outputs.push(capacity_cell(top_bidder_lock, refund));
+                outputs_data.push(Bytes::new());
+            }
+        }
+        Action::Settle => {
+            let paid = if error == DexError::SellerUnderpaid {
+                TOP_BID as u64 - 1
+            } else {
+                TOP_BID as u64
+            };
+            let winner_lock = if error == DexError::WinnerNotMatch {
+                new_bidder_lock
+            } else {
+                top_bidder_lock
+            };
+            let winner_capacity = if error == DexError::WinnerCapacitySkimmed {
+                ASSET_CAPACITY - 1
+            } else {
+                ASSET_CAPACITY
+            };
+            outputs.push(capacity_cell(owner_lock, paid));
+            outputs.push(asset_cell(winner_lock, winner_capacity));
+            outputs_data.push(Bytes::new());
+            outputs_data.push(asset_data);
+        }
+        Action::Return | Action::OwnerCancel => {
+            if action == Action::OwnerCancel {
+                let owner_out_point = context.create_cell(
+                    capacity_cell(owner_lock.clone(), 100_0000_0000),
+                    Bytes::new(),
+                );
+                inputs.push(
+                    CellInput::new_builder()
+                        .previous_output(owner_out_point)
+                        .build(),
+                );
+            }
+            outputs.push(asset_cell(owner_lock, input_capacity));
+            outputs_data.push(asset_data);
+        }
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_english_auction_success() {
+    let actions = [
+        Action::FirstBid,
+        Action::Outbid,
+        Action::Settle,
+        Action::Return,
+        Action::OwnerCancel,
+    ];
+    for action in actions {
+        let (context, tx) = create_test_context(action, DexError::NoError);
+        // run
+        let cycles = context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification");
+        println!("consume cycles: {}", cycles);
+    }
+}
+
+#[test]
+fn test_dex_english_auction_bid_error() {
+    let cases = [
+        (Action::FirstBid, DexError::BidTooLow),
+        (Action::Outbid, DexError::BidTooLow),
+        (Action::FirstBid, DexError::EscrowNotEnough),
+        (Action::Outbid, DexError::EscrowNotEnough),
+        (Action::Outbid, DexError::DeadlineChanged),
+    ];
+    for (action, error) in cases {
+        let (context, tx) = create_test_context(action, error);
+        // run
+        let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+        assert_script_error(err, DexLockError::DexAuctionBidInvalid);
+    }
+}
+
+#[test]
+fn test_dex_english_auction_refund_not_match_error() {
+    let (context, tx) = create_test_context(Action::Outbid, DexError::RefundNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionRefundNotMatch);
+}
+
+#[test]
+fn test_dex_english_auction_winner_not_match_error() {
+    let (context, tx) = create_test_context(Action::Settle, DexError::WinnerNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionWinnerNotMatch);
+}
+
+#[test]
+fn test_dex_english_auction_winner_capacity_skimmed_error() {
+    let (context, tx) = create_test_context(Action::Settle, DexError::WinnerCapacitySkimmed);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionWinnerNotMatch);
+}
+
+#[test]
+fn test_dex_english_auction_seller_underpaid_error() {
+    let (context, tx) = create_test_context(Action::Settle, DexError::SellerUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_english_auction_settle_before_deadline_error() {
+    let (context, tx) = create_test_context(Action::Settle, DexError::BeforeDeadline);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionBidInvalid);
+}
+
+#[test]
+fn test_dex_english_auction_faked_bid_error() {
+    for action in [Action::Outbid, Action::Settle] {
+        let (context, tx) = create_test_context(action, DexError::FakedBid);
+        // run
+        let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+        assert_script_error(err, DexLockError::DexAuctionInvalid);
+    }
+}
+
+#[test]
+fn test_dex_english_auction_owner_cancel_after_bid_error() {
+    // the owner cannot take back the escrowed bid
+    let (context, tx) = create_test_context(Action::OwnerCancel, DexError::CancelAfterBid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexAuctionBidInvalid);
+}
@@ -0,0 +1,148 @@
+use dex_errors::{
+    AmountReaderError, Contract, ContractError, DexLockError, ErrorRegistry, ScriptCodeHash,
+    ScriptFailure, ScriptField, ScriptSource, SudtError,
+};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// a UDT transfer whose outputs hold more tokens than its inputs
+fn create_test_context() -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let holder_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(rng.gen::<[u8; 32]>().to_vec()))
+        .expect("script");
+
+    let fee_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500_0000_0000u64.pack())
+            .lock(holder_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let udt_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(200_0000_0000u64.pack())
+            .lock(holder_lock.clone())
+            .type_(Some(sudt_type_script.clone()).pack())
+            .build(),
+        Bytes::from(100u128.to_le_bytes().to_vec()),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(fee_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(udt_out_point)
+            .build(),
+    ];
+    let outputs = vec![CellOutput::new_builder()
+        .capacity(200_0000_0000u64.pack())
+        .lock(holder_lock)
+        .type_(Some(sudt_type_script).pack())
+        .build()];
+    let outputs_data = vec![Bytes::from(101u128.to_le_bytes().to_vec())];
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_script_failure_decode() {
+    let (context, tx) = create_test_context();
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let failure = ScriptFailure::parse(&err.to_string()).expect("script failure");
+    assert_eq!(failure.source, ScriptSource::Inputs);
+    assert_eq!(failure.index, 1);
+    assert_eq!(failure.field, ScriptField::Type);
+
+    let sudt_type = tx.outputs().get(0).unwrap().type_().to_opt().unwrap();
+    let code_hash = ScriptCodeHash::Type(sudt_type.code_hash().unpack());
+    assert_eq!(failure.code_hash, code_hash);
+    // the code 13 of sudt is another error in the catalogue of dex-lock
+    let registry = ErrorRegistry::new().register(code_hash, Contract::Sudt);
+    assert_eq!(
+        registry.decode(&failure),
+        Some(ContractError::Sudt(SudtError::InvalidAmount))
+    );
+    assert_eq!(ErrorRegistry::new().decode(&failure), None);
+}
+
+#[test]
+fn test_script_failure_decode_not_script_error() {
+    assert_eq!(
+        ScriptFailure::parse("Transaction(InsufficientCellCapacity)"),
+        None
+    );
+}
+
+#[test]
+fn test_error_codes_stable() {
+    // the codes are part of the interface of the contracts and must never change
+    assert_eq!(i8::from(DexLockError::LockArgsInvalid), 5);
+    assert_eq!(i8::from(DexLockError::TotalValueOverflow), 10);
+    assert_eq!(i8::from(DexLockError::DexOrderExpired), 20);
+    assert_eq!(i8::from(DexLockError::DexAuctionBidInvalid), 30);
+    assert_eq!(i8::from(DexLockError::DexAssetNotMatch), 35);
+    assert_eq!(i8::from(DexLockError::SysErrorUnknown), 36);
+    assert_eq!(i8::from(DexLockError::WaitFailure), 37);
+    assert_eq!(i8::from(DexLockError::MaxVmsSpawned), 40);
+    assert_eq!(i8::from(DexLockError::MaxFdsCreated), 41);
+    assert_eq!(i8::from(DexLockError::DexAmountReaderFailed), 43);
+    assert_eq!(i8::from(SudtError::AmountEncoding), 12);
+    assert_eq!(i8::from(SudtError::SysErrorUnknown), 14);
+    assert_eq!(i8::from(AmountReaderError::AmountEncoding), 10);
+
+    for code in 1..=43 {
+        let err = DexLockError::from_code(code).expect("dex-lock error");
+        assert_eq!(err.code(), code);
+        assert!(!err.to_string().is_empty());
+    }
+    assert_eq!(DexLockError::from_code(0), None);
+    assert_eq!(DexLockError::from_code(44), None);
+    assert_eq!(SudtError::from_code(9), None);
+}
@@ -0,0 +1,238 @@
+use dex_errors::DexLockError;
+use dex_types::{since, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const EXPIRY_BLOCK_NUMBER: u64 = 1_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Settlement {
+    // the buyer pays the order
+    Take,
+    // the asset is returned unchanged to the owner
+    Return,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    Expired,
+    NotExpired,
+    // the since is a timestamp but the expiry is a block number
+    MetricNotMatch,
+    ReturnDataChanged,
+    ExpiryInvalid,
+}
+
+fn create_test_context(settlement: Settlement, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let total_value = 1234_5678_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .expiry(since::absolute_block_number(EXPIRY_BLOCK_NUMBER))
+        .build()
+        .unwrap();
+    let mut dex_args_vec = dex_args.to_vec().unwrap();
+    if error == DexError::ExpiryInvalid {
+        // the encoder refuses relative expiries, so set the flag of the expiry by hand
+        let expiry_pos = dex_args.extension_pos() + 2;
+        dex_args_vec[expiry_pos] |= 0b1000_0000;
+    }
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args_vec.into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    // the since of the DEX input tells whether the order is expired
+    let dex_since = match error {
+        DexError::NotExpired => since::absolute_block_number(EXPIRY_BLOCK_NUMBER - 1),
+        DexError::MetricNotMatch => since::absolute_timestamp(EXPIRY_BLOCK_NUMBER),
+        DexError::NoError if settlement == Settlement::Take => 0,
+        _ => since::absolute_block_number(EXPIRY_BLOCK_NUMBER),
+    };
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_out_point)
+            .since(dex_since.pack())
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let (outputs, outputs_data) = match settlement {
+        Settlement::Take => (
+            vec![
+                CellOutput::new_builder()
+                    .capacity((total_value as u64 + dex_capacity).pack())
+                    .lock(owner_lock)
+                    .build(),
+                CellOutput::new_builder()
+                    .capacity(dex_capacity.pack())
+                    .lock(buyer_lock)
+                    .type_(Some(asset_type).pack())
+                    .build(),
+            ],
+            vec![Bytes::new(), asset_data],
+        ),
+        Settlement::Return => {
+            let return_data = if error == DexError::ReturnDataChanged {
+                Bytes::from(1u128.to_le_bytes().to_vec())
+            } else {
+                asset_data
+            };
+            (
+                vec![
+                    CellOutput::new_builder()
+                        .capacity(dex_capacity.pack())
+                        .lock(owner_lock)
+                        .type_(Some(asset_type).pack())
+                        .build(),
+                    CellOutput::new_builder()
+                        .capacity(1999_0000_0000u64.pack())
+                        .lock(buyer_lock)
+                        .build(),
+                ],
+                vec![return_data, Bytes::new()],
+            )
+        }
+    };
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_expiry_take_before_expiry_success() {
+    let (context, tx) = create_test_context(Settlement::Take, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_expiry_take_after_expiry_error() {
+    let (context, tx) = create_test_context(Settlement::Take, DexError::Expired);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexOrderExpired);
+}
+
+#[test]
+fn test_dex_expiry_return_after_expiry_success() {
+    let (context, tx) = create_test_context(Settlement::Return, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_expiry_return_before_expiry_error() {
+    let (context, tx) = create_test_context(Settlement::Return, DexError::NotExpired);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_expiry_return_with_other_metric_error() {
+    let (context, tx) = create_test_context(Settlement::Return, DexError::MetricNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_expiry_return_data_changed_error() {
+    let (context, tx) = create_test_context(Settlement::Return, DexError::ReturnDataChanged);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexOrderExpired);
+}
+
+#[test]
+fn test_dex_expiry_invalid_error() {
+    let (context, tx) = create_test_context(Settlement::Return, DexError::ExpiryInvalid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexExpiryInvalid);
+}
@@ -1,17 +1,59 @@
 use ckb_testtool::ckb_error::Error;
 use ckb_testtool::ckb_types::bytes::Bytes;
+use dex_errors::ScriptFailure;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+#[cfg(test)]
+mod aggregate_payment_tests;
+#[cfg(test)]
+mod alt_units_tests;
+#[cfg(test)]
+mod amount_format_tests;
+#[cfg(test)]
+mod amount_reader_tests;
+#[cfg(test)]
+mod args_tests;
+#[cfg(test)]
+mod asset_commitment_tests;
+#[cfg(test)]
+mod bid_tests;
+#[cfg(test)]
+mod bundle_tests;
 #[cfg(test)]
 mod cancel_tests;
-mod helper;
+#[cfg(test)]
+mod dutch_auction_tests;
+#[cfg(test)]
+mod english_auction_tests;
+#[cfg(test)]
+mod error_tests;
+#[cfg(test)]
+mod expiry_tests;
+#[cfg(test)]
+mod nft_tests;
+#[cfg(test)]
+mod partial_fill_tests;
+#[cfg(test)]
+mod platform_fee_tests;
+#[cfg(test)]
+mod receiver_tests;
+#[cfg(test)]
+mod reprice_tests;
+#[cfg(test)]
+mod royalty_tests;
+#[cfg(test)]
+mod scoped_cancel_tests;
+#[cfg(test)]
+mod taker_lock_tests;
 #[cfg(test)]
 mod taker_tests;
 #[cfg(test)]
 mod taker_udt_tests;
+#[cfg(test)]
+mod tx_builder_tests;
 
 const TEST_ENV_VAR: &str = "CAPSULE_TEST_ENV";
 
@@ -66,10 +108,12 @@ impl Loader {
     }
 }
 
-pub fn assert_script_error(err: Error, err_code: i8) {
+pub fn assert_script_error(err: Error, err_code: impl Into<i8>) {
     let error_string = err.to_string();
+    let err_code = err_code.into();
+    let failure = ScriptFailure::parse(&error_string);
     assert!(
-        error_string.contains(format!("error code {} ", err_code).as_str()),
+        failure.is_some_and(|failure| failure.code == err_code),
         "error_string: {}, expected_error_code: {}",
         error_string,
         err_code
@@ -0,0 +1,213 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // the args record the type hash of another NFT
+    ListedTypeNotMatch,
+    NftNotTransferred,
+    NftDuplicated,
+    PaymentWithType,
+    PaymentWithData,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    // the args of the NFT type script are the id of the token
+    let nft_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .args(Bytes::from(rng.gen::<[u8; 32]>().to_vec()).pack())
+        .build();
+    let mut nft_type_hash = [0u8; 32];
+    nft_type_hash.copy_from_slice(nft_type.calc_script_hash().as_slice());
+    if error == DexError::ListedTypeNotMatch {
+        nft_type_hash = rng.gen::<[u8; 32]>();
+    }
+
+    let total_value = 800_0000_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .nft_type_hash(nft_type_hash)
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let dex_capacity = 200_0000_0000u64;
+    let nft_data = Bytes::from(vec![7u8; 40]);
+    let dex_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(nft_type.clone()).pack())
+            .build(),
+        nft_data.clone(),
+    );
+    let buyer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000_0000_0000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let payment_type = if error == DexError::PaymentWithType {
+        Some(
+            ScriptBuilder::default()
+                .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+                .hash_type(Byte::from(2u8))
+                .build(),
+        )
+    } else {
+        None
+    };
+    let payment_data = if error == DexError::PaymentWithData {
+        Bytes::from(vec![1u8])
+    } else {
+        Bytes::new()
+    };
+    let mut outputs = vec![CellOutput::new_builder()
+        .capacity((total_value as u64).pack())
+        .lock(owner_lock)
+        .type_(payment_type.pack())
+        .build()];
+    let mut outputs_data = vec![payment_data];
+    let nft_output = CellOutput::new_builder()
+        .capacity(dex_capacity.pack())
+        .lock(buyer_lock)
+        .type_(Some(nft_type).pack())
+        .build();
+    match error {
+        DexError::NftNotTransferred => {}
+        DexError::NftDuplicated => {
+            outputs.push(nft_output.clone());
+            outputs.push(nft_output);
+            outputs_data.push(nft_data.clone());
+            outputs_data.push(nft_data);
+        }
+        _ => {
+            outputs.push(nft_output);
+            outputs_data.push(nft_data);
+        }
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_nft_type_hash_order_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_nft_type_hash_listed_type_not_match_error() {
+    let (context, tx) = create_test_context(DexError::ListedTypeNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTypeNotMatch);
+}
+
+#[test]
+fn test_dex_nft_type_hash_not_transferred_error() {
+    let (context, tx) = create_test_context(DexError::NftNotTransferred);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTypeNotMatch);
+}
+
+#[test]
+fn test_dex_nft_type_hash_duplicated_error() {
+    let (context, tx) = create_test_context(DexError::NftDuplicated);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTypeNotMatch);
+}
+
+#[test]
+fn test_dex_nft_type_hash_payment_with_type_error() {
+    let (context, tx) = create_test_context(DexError::PaymentWithType);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTPaymentInvalid);
+}
+
+#[test]
+fn test_dex_nft_type_hash_payment_with_data_error() {
+    let (context, tx) = create_test_context(DexError::PaymentWithData);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTPaymentInvalid);
+}
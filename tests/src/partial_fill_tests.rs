@@ -0,0 +1,323 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgs;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Settlement {
+    Ckb,
+    Udt,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    PartialFillNotAllowed,
+    NftPartialFill,
+    Underpaid,
+    LeftoverTotalValueNotMatch,
+    LeftoverAmountInvalid,
+    LeftoverTypeNotMatch,
+}
+
+fn create_test_context(settlement: Settlement, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![42]))
+        .expect("script");
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(sudt_type_script.calc_script_hash().as_slice());
+
+    let asset_amount = 1000_0000_0000u128;
+    let sold_amount = 300_0000_0000u128;
+    let leftover_amount = if error == DexError::LeftoverAmountInvalid {
+        asset_amount
+    } else {
+        asset_amount - sold_amount
+    };
+    let total_value = 1234_5678_0001u128;
+    // the sold tokens are paid at the order price rounded up
+    let paid_value = (total_value * sold_amount + asset_amount - 1) / asset_amount;
+    let leftover_total_value = if error == DexError::LeftoverTotalValueNotMatch {
+        total_value - paid_value + 1
+    } else {
+        total_value - paid_value
+    };
+
+    let mut setup = match settlement {
+        Settlement::Ckb => 0b0000_1000u8,
+        Settlement::Udt => 0b0000_1010u8,
+    };
+    if error == DexError::PartialFillNotAllowed {
+        setup &= !0b0000_1000;
+    }
+    let dex_args = DexArgs {
+        owner_lock: owner_lock.as_slice().to_vec(),
+        setup,
+        total_value,
+        receiver_lock: None,
+        unit_type_hash: (settlement == Settlement::Udt).then_some(unit_type_hash),
+        extension: None,
+    };
+    let mut dex_args_vec = dex_args.to_vec().unwrap();
+    if error == DexError::NftPartialFill {
+        // the encoder refuses partially filled NFT orders, so set the setup byte by hand
+        dex_args_vec[owner_lock.as_slice().len()] |= 0b0000_0100;
+    }
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args_vec.into())
+        .expect("script");
+    let leftover_args = DexArgs {
+        total_value: leftover_total_value,
+        ..dex_args.clone()
+    };
+    let leftover_lock_script = context
+        .build_script(&dex_out_point, leftover_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+    let other_asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .args(Bytes::from(vec![1u8]).pack())
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let dex_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        Bytes::from(asset_amount.to_le_bytes().to_vec()),
+    );
+
+    let buyer_token = 4000_0000_0000u128;
+    let buyer_out_point = match settlement {
+        Settlement::Ckb => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(2000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(buyer_token.to_le_bytes().to_vec()),
+        ),
+    };
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let payment_value = if error == DexError::Underpaid {
+        paid_value - 1
+    } else {
+        paid_value
+    };
+    let (payment_output, payment_data) = match settlement {
+        Settlement::Ckb => (
+            CellOutput::new_builder()
+                .capacity((payment_value as u64).pack())
+                .lock(owner_lock.clone())
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(owner_lock.clone())
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(payment_value.to_le_bytes().to_vec()),
+        ),
+    };
+    let leftover_type = if error == DexError::LeftoverTypeNotMatch {
+        other_asset_type
+    } else {
+        asset_type.clone()
+    };
+
+    let mut outputs = vec![
+        payment_output,
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(leftover_lock_script)
+            .type_(Some(leftover_type).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+    ];
+    let mut outputs_data = vec![
+        payment_data,
+        Bytes::from(leftover_amount.to_le_bytes().to_vec()),
+        Bytes::from(sold_amount.to_le_bytes().to_vec()),
+    ];
+    if settlement == Settlement::Udt {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock)
+                .type_(Some(sudt_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            (buyer_token - payment_value).to_le_bytes().to_vec(),
+        ));
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_partial_fill_ckb_order_success() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_partial_fill_udt_order_success() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_partial_fill_not_allowed_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::PartialFillNotAllowed);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_partial_fill_nft_setup_invalid_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::NftPartialFill);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexSetupInvalid);
+}
+
+#[test]
+fn test_dex_partial_fill_ckb_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_partial_fill_udt_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::Underpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_partial_fill_leftover_total_value_not_match_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::LeftoverTotalValueNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexPartialFillTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_partial_fill_leftover_amount_invalid_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::LeftoverAmountInvalid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexPartialFillAmountInvalid);
+}
+
+#[test]
+fn test_dex_partial_fill_leftover_type_not_match_error() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::LeftoverTypeNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexPartialFillInvalid);
+}
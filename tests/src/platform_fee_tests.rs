@@ -0,0 +1,338 @@
+use dex_errors::DexLockError;
+use dex_types::{DexArgsBuilder, BASIS_POINTS};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const ROYALTY_RATE: u16 = 500;
+const PLATFORM_FEE_RATE: u16 = 125;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Settlement {
+    // an NFT order priced in CKB
+    Ckb,
+    // a fungible token order priced in the UDT
+    Udt,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Fees {
+    Free,
+    PlatformFee,
+    // the platform fee output follows the royalty output
+    RoyaltyAndPlatformFee,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    PlatformFeeUnderpaid,
+    PlatformFeeLockNotMatch,
+    // the platform fee output comes before the royalty output
+    SharesSwapped,
+    SellerUnderpaid,
+}
+
+fn create_test_context(
+    settlement: Settlement,
+    fees: Fees,
+    error: DexError,
+) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![42]))
+        .expect("script");
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let mut random_lock = || {
+        context
+            .build_script(
+                &always_success_out_point,
+                rng.gen::<[u8; 20]>().to_vec().into(),
+            )
+            .expect("script")
+    };
+    let owner_lock = random_lock();
+    let royalty_lock = random_lock();
+    let platform_lock = random_lock();
+    let buyer_lock = random_lock();
+
+    let lock_hash = |lock: &Script| {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(lock.calc_script_hash().as_slice());
+        hash
+    };
+    let unit_type_hash = lock_hash(&sudt_type_script);
+
+    let total_value = 1234_5678_0001u128;
+    let share_value = |rate: u16| total_value * rate as u128 / BASIS_POINTS as u128;
+    let mut builder = DexArgsBuilder::new(owner_lock.as_slice(), total_value);
+    builder = match settlement {
+        Settlement::Ckb => builder.nft(),
+        Settlement::Udt => builder.unit_type_hash(unit_type_hash),
+    };
+    // the shares in the order of their outputs
+    let mut shares = vec![];
+    if fees == Fees::RoyaltyAndPlatformFee {
+        builder = builder.royalty(lock_hash(&royalty_lock), ROYALTY_RATE);
+        shares.push((royalty_lock, share_value(ROYALTY_RATE)));
+    }
+    if fees != Fees::Free {
+        builder = builder.platform_fee(lock_hash(&platform_lock), PLATFORM_FEE_RATE);
+        shares.push((platform_lock, share_value(PLATFORM_FEE_RATE)));
+    }
+    let dex_args = builder.build().unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+    let seller_value = total_value - shares.iter().map(|(_, value)| value).sum::<u128>();
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+
+    let buyer_token = 4000_0000_0000u128;
+    let buyer_out_point = match settlement {
+        Settlement::Ckb => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(5000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(buyer_token.to_le_bytes().to_vec()),
+        ),
+    };
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    match error {
+        DexError::PlatformFeeUnderpaid => shares.last_mut().unwrap().1 -= 1,
+        DexError::PlatformFeeLockNotMatch => shares.last_mut().unwrap().0 = owner_lock.clone(),
+        DexError::SharesSwapped => shares.reverse(),
+        _ => {}
+    }
+    let seller_value = if error == DexError::SellerUnderpaid {
+        seller_value - 1
+    } else {
+        seller_value
+    };
+    let payment = |lock: Script, value: u128| match settlement {
+        Settlement::Ckb => (
+            CellOutput::new_builder()
+                .capacity((value as u64).pack())
+                .lock(lock)
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(lock)
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(value.to_le_bytes().to_vec()),
+        ),
+    };
+
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    let mut paid_value = seller_value;
+    let mut payments = vec![payment(owner_lock, seller_value)];
+    for (lock, value) in shares {
+        paid_value += value;
+        payments.push(payment(lock, value));
+    }
+    for (output, data) in payments {
+        outputs.push(output);
+        outputs_data.push(data);
+    }
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+    );
+    outputs_data.push(asset_data);
+    if settlement == Settlement::Udt {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock)
+                .type_(Some(sudt_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            (buyer_token - paid_value).to_le_bytes().to_vec(),
+        ));
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_platform_fee_ckb_order_success() {
+    for fees in [Fees::Free, Fees::PlatformFee, Fees::RoyaltyAndPlatformFee] {
+        let (context, tx) = create_test_context(Settlement::Ckb, fees, DexError::NoError);
+        // run
+        let cycles = context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification");
+        println!("consume cycles: {}", cycles);
+    }
+}
+
+#[test]
+fn test_dex_platform_fee_udt_order_success() {
+    for fees in [Fees::Free, Fees::PlatformFee, Fees::RoyaltyAndPlatformFee] {
+        let (context, tx) = create_test_context(Settlement::Udt, fees, DexError::NoError);
+        // run
+        let cycles = context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification");
+        println!("consume cycles: {}", cycles);
+    }
+}
+
+#[test]
+fn test_dex_platform_fee_ckb_underpaid_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Ckb,
+        Fees::PlatformFee,
+        DexError::PlatformFeeUnderpaid,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareValueNotMatch);
+}
+
+#[test]
+fn test_dex_platform_fee_udt_underpaid_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Udt,
+        Fees::RoyaltyAndPlatformFee,
+        DexError::PlatformFeeUnderpaid,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareValueNotMatch);
+}
+
+#[test]
+fn test_dex_platform_fee_lock_not_match_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Udt,
+        Fees::PlatformFee,
+        DexError::PlatformFeeLockNotMatch,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareLockNotMatch);
+}
+
+#[test]
+fn test_dex_platform_fee_before_royalty_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Ckb,
+        Fees::RoyaltyAndPlatformFee,
+        DexError::SharesSwapped,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareLockNotMatch);
+}
+
+#[test]
+fn test_dex_platform_fee_ckb_seller_underpaid_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Ckb,
+        Fees::RoyaltyAndPlatformFee,
+        DexError::SellerUnderpaid,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_platform_fee_udt_seller_underpaid_error() {
+    let (context, tx) = create_test_context(
+        Settlement::Udt,
+        Fees::PlatformFee,
+        DexError::SellerUnderpaid,
+    );
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
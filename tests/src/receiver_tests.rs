@@ -0,0 +1,266 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgs;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Settlement {
+    Ckb,
+    Udt,
+    Nft,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    PaidToOwner,
+}
+
+fn create_test_context(settlement: Settlement, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![42]))
+        .expect("script");
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let receiver_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut receiver_lock_hash = [0u8; 32];
+    receiver_lock_hash.copy_from_slice(receiver_lock.calc_script_hash().as_slice());
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(sudt_type_script.calc_script_hash().as_slice());
+
+    let total_value = 1234_5678_0000u128;
+    let (setup, unit_type_hash) = match settlement {
+        Settlement::Ckb => (0b0000_0001u8, None),
+        Settlement::Udt => (0b0000_0011u8, Some(unit_type_hash)),
+        Settlement::Nft => (0b0000_0101u8, None),
+    };
+    let dex_args = DexArgs {
+        owner_lock: owner_lock.as_slice().to_vec(),
+        setup,
+        total_value,
+        receiver_lock: Some(receiver_lock_hash),
+        unit_type_hash,
+        extension: None,
+    };
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+
+    let buyer_token = 4000_0000_0000u128;
+    let buyer_out_point = if settlement == Settlement::Udt {
+        context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(buyer_token.to_le_bytes().to_vec()),
+        )
+    } else {
+        context.create_cell(
+            CellOutput::new_builder()
+                .capacity(2000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        )
+    };
+
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    ];
+
+    let payment_lock = if error == DexError::PaidToOwner {
+        owner_lock
+    } else {
+        receiver_lock
+    };
+    let (payment_output, payment_data) = match settlement {
+        Settlement::Ckb => (
+            CellOutput::new_builder()
+                .capacity((total_value as u64 + dex_capacity).pack())
+                .lock(payment_lock)
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(payment_lock)
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(total_value.to_le_bytes().to_vec()),
+        ),
+        Settlement::Nft => (
+            CellOutput::new_builder()
+                .capacity((total_value as u64).pack())
+                .lock(payment_lock)
+                .build(),
+            Bytes::new(),
+        ),
+    };
+
+    let mut outputs = vec![
+        payment_output,
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+    ];
+    let mut outputs_data = vec![payment_data, asset_data];
+    if settlement == Settlement::Udt {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock)
+                .type_(Some(sudt_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            (buyer_token - total_value).to_le_bytes().to_vec(),
+        ));
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_receiver_ckb_order_success() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_receiver_udt_order_success() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_receiver_nft_order_success() {
+    let (context, tx) = create_test_context(Settlement::Nft, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_receiver_ckb_order_paid_to_owner_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::PaidToOwner);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexReceiverLockNotMatch);
+}
+
+#[test]
+fn test_dex_receiver_udt_order_paid_to_owner_error() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::PaidToOwner);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexReceiverLockNotMatch);
+}
+
+#[test]
+fn test_dex_receiver_nft_order_paid_to_owner_error() {
+    let (context, tx) = create_test_context(Settlement::Nft, DexError::PaidToOwner);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexReceiverLockNotMatch);
+}
@@ -0,0 +1,222 @@
+use dex_errors::DexLockError;
+use dex_types::{witness, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // the receiver lock is added along with the new price
+    ArgsChanged,
+    DataChanged,
+    CapacityReduced,
+    // the order is cancelled to the owner lock instead of repriced
+    NotRelocked,
+    OwnerCellMissing,
+}
+
+fn create_test_context(scoped_cancel: bool, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let builder = if scoped_cancel {
+        DexArgsBuilder::new(owner_lock.as_slice(), 1234_5678_0000u128).scoped_cancel()
+    } else {
+        DexArgsBuilder::new(owner_lock.as_slice(), 1234_5678_0000u128)
+            .expiry(dex_types::since::absolute_block_number(1000))
+    };
+    let dex_args = builder.clone().build().unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+    let mut repriced_args = dex_args.clone();
+    repriced_args.total_value = 1000_0000_0000u128;
+    if error == DexError::ArgsChanged {
+        repriced_args = builder
+            .receiver_lock(rng.gen::<[u8; 32]>())
+            .build()
+            .unwrap();
+        repriced_args.total_value = 1000_0000_0000u128;
+    }
+    let repriced_lock_script = context
+        .build_script(&dex_out_point, repriced_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+    let owner_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(100_0000_0000u64.pack())
+            .lock(owner_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    let mut inputs = vec![CellInput::new_builder()
+        .previous_output(dex_input_out_point)
+        .build()];
+    if error != DexError::OwnerCellMissing {
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(owner_out_point)
+                .build(),
+        );
+    }
+
+    let output_lock = if error == DexError::NotRelocked {
+        owner_lock.clone()
+    } else {
+        repriced_lock_script
+    };
+    let output_capacity = if error == DexError::CapacityReduced {
+        dex_capacity - 1
+    } else {
+        dex_capacity
+    };
+    let output_data = if error == DexError::DataChanged {
+        Bytes::from(999_0000_0000u128.to_le_bytes().to_vec())
+    } else {
+        asset_data
+    };
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(output_capacity.pack())
+            .lock(output_lock)
+            .type_(Some(asset_type).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(99_0000_0000u64.pack())
+            .lock(owner_lock.clone())
+            .build(),
+    ];
+    let outputs_data = vec![output_data, Bytes::new()];
+
+    // the witness of the repriced order carries the reprice mark in its lock field
+    let mut owner_lock_hash = [0u8; 32];
+    owner_lock_hash.copy_from_slice(owner_lock.calc_script_hash().as_slice());
+    let mark = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(witness::reprice_mark(owner_lock_hash).to_vec())).pack())
+        .build()
+        .as_bytes();
+    let mut witnesses = vec![mark];
+    for _ in 1..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_reprice_success() {
+    let (context, tx) = create_test_context(false, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_reprice_scoped_cancel_order_success() {
+    let (context, tx) = create_test_context(true, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_reprice_args_changed_error() {
+    let (context, tx) = create_test_context(false, DexError::ArgsChanged);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexRepriceInvalid);
+}
+
+#[test]
+fn test_dex_reprice_data_changed_error() {
+    let (context, tx) = create_test_context(true, DexError::DataChanged);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexRepriceInvalid);
+}
+
+#[test]
+fn test_dex_reprice_capacity_reduced_error() {
+    let (context, tx) = create_test_context(false, DexError::CapacityReduced);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexRepriceInvalid);
+}
+
+#[test]
+fn test_dex_reprice_not_relocked_error() {
+    let (context, tx) = create_test_context(false, DexError::NotRelocked);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexRepriceInvalid);
+}
+
+#[test]
+fn test_dex_reprice_owner_cell_missing_error() {
+    let (context, tx) = create_test_context(true, DexError::OwnerCellMissing);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexCancelNotAuthorized);
+}
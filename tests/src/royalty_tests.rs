@@ -0,0 +1,338 @@
+use dex_errors::DexLockError;
+use dex_types::{DexArgsBuilder, BASIS_POINTS};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const ROYALTY_RATE: u16 = 250;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Settlement {
+    // an NFT order priced in CKB
+    Ckb,
+    // a fungible token order priced in the UDT
+    Udt,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // the royalty is rounded down to zero and needs no output
+    RoyaltyRoundedToZero,
+    RoyaltyUnderpaid,
+    RoyaltyLockNotMatch,
+    SellerUnderpaid,
+    // the royalty output is also the payment of another order
+    OutputIndexConflict,
+}
+
+fn create_test_context(settlement: Settlement, error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+    let sudt_out_point = context.deploy_cell(sudt_bin);
+    let sudt_dep = CellDep::new_builder()
+        .out_point(sudt_out_point.clone())
+        .build();
+    let sudt_type_script = context
+        .build_script(&sudt_out_point, Bytes::from(vec![42]))
+        .expect("script");
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let royalty_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut royalty_lock_hash = [0u8; 32];
+    royalty_lock_hash.copy_from_slice(royalty_lock.calc_script_hash().as_slice());
+    let mut unit_type_hash = [0u8; 32];
+    unit_type_hash.copy_from_slice(sudt_type_script.calc_script_hash().as_slice());
+
+    let total_value = if error == DexError::RoyaltyRoundedToZero {
+        39u128
+    } else {
+        1234_5678_0001u128
+    };
+    // the royalty is rounded down and the seller receives the rest
+    let royalty_value = total_value * ROYALTY_RATE as u128 / BASIS_POINTS as u128;
+    let seller_value = total_value - royalty_value;
+
+    let builder = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .royalty(royalty_lock_hash, ROYALTY_RATE);
+    let dex_args = match settlement {
+        Settlement::Ckb => builder.nft(),
+        Settlement::Udt => builder.unit_type_hash(unit_type_hash),
+    }
+    .build()
+    .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+
+    let buyer_token = 4000_0000_0000u128;
+    let buyer_out_point = match settlement {
+        Settlement::Ckb => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(5000_0000_0000u64.pack())
+                .lock(buyer_lock.clone())
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock.clone())
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(buyer_token.to_le_bytes().to_vec()),
+        ),
+    };
+
+    let mut inputs = vec![CellInput::new_builder()
+        .previous_output(dex_input_out_point)
+        .build()];
+    if error == DexError::OutputIndexConflict {
+        // an NFT order of the royalty receiver whose price is the royalty
+        let conflict_args = DexArgsBuilder::new(royalty_lock.as_slice(), royalty_value)
+            .nft()
+            .build()
+            .unwrap();
+        let conflict_lock_script = context
+            .build_script(&dex_out_point, conflict_args.to_vec().unwrap().into())
+            .expect("script");
+        let conflict_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(conflict_lock_script)
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            asset_data.clone(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(conflict_out_point)
+                .build(),
+        );
+    }
+    inputs.push(
+        CellInput::new_builder()
+            .previous_output(buyer_out_point)
+            .build(),
+    );
+
+    let seller_value = if error == DexError::SellerUnderpaid {
+        seller_value - 1
+    } else {
+        seller_value
+    };
+    let royalty_value = if error == DexError::RoyaltyUnderpaid {
+        royalty_value - 1
+    } else {
+        royalty_value
+    };
+    let royalty_lock = if error == DexError::RoyaltyLockNotMatch {
+        owner_lock.clone()
+    } else {
+        royalty_lock
+    };
+    let payment = |lock: Script, value: u128| match settlement {
+        Settlement::Ckb => (
+            CellOutput::new_builder()
+                .capacity((value as u64).pack())
+                .lock(lock)
+                .build(),
+            Bytes::new(),
+        ),
+        Settlement::Udt => (
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(lock)
+                .type_(Some(sudt_type_script.clone()).pack())
+                .build(),
+            Bytes::from(value.to_le_bytes().to_vec()),
+        ),
+    };
+
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    let mut payments = vec![payment(owner_lock, seller_value)];
+    if error != DexError::RoyaltyRoundedToZero {
+        payments.push(payment(royalty_lock, royalty_value));
+    }
+    for (output, data) in payments {
+        outputs.push(output);
+        outputs_data.push(data);
+    }
+    outputs.push(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+    );
+    outputs_data.push(asset_data);
+    if settlement == Settlement::Udt {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(buyer_lock)
+                .type_(Some(sudt_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            (buyer_token - seller_value - royalty_value)
+                .to_le_bytes()
+                .to_vec(),
+        ));
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep, sudt_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_royalty_ckb_order_success() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_royalty_udt_order_success() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_royalty_rounded_to_zero_success() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::RoyaltyRoundedToZero);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_royalty_ckb_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::RoyaltyUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareValueNotMatch);
+}
+
+#[test]
+fn test_dex_royalty_udt_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::RoyaltyUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareValueNotMatch);
+}
+
+#[test]
+fn test_dex_royalty_lock_not_match_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::RoyaltyLockNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexShareLockNotMatch);
+}
+
+#[test]
+fn test_dex_royalty_ckb_seller_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::SellerUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_royalty_udt_seller_underpaid_error() {
+    let (context, tx) = create_test_context(Settlement::Udt, DexError::SellerUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_royalty_output_index_conflict_error() {
+    let (context, tx) = create_test_context(Settlement::Ckb, DexError::OutputIndexConflict);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexOutputIndexConflict);
+}
@@ -0,0 +1,209 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // only the first order is marked, the second one is taken without payment
+    OneOrderMarked,
+    // the owner cell alone does not cancel scoped orders
+    NoOrderMarked,
+    OwnerCellMissing,
+    MarkerNotMatch,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let dex_args1 = DexArgsBuilder::new(owner_lock.as_slice(), 1234_5678_0000u128)
+        .scoped_cancel()
+        .build()
+        .unwrap();
+    let dex_lock_script1 = context
+        .build_script(&dex_out_point, dex_args1.to_vec().unwrap().into())
+        .expect("script");
+    let dex_args2 = DexArgsBuilder::new(owner_lock.as_slice(), 9_8765_0000_1234u128)
+        .scoped_cancel()
+        .build()
+        .unwrap();
+    let dex_lock_script2 = context
+        .build_script(&dex_out_point, dex_args2.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let input_out_point1 = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(300_0000_0000u64.pack())
+            .lock(dex_lock_script1)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let input_out_point2 = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(240_0000_0000u64.pack())
+            .lock(dex_lock_script2)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        Bytes::from(3456_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let input_out_point3 = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(100_0000_0000u64.pack())
+            .lock(owner_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    let mut inputs = vec![
+        CellInput::new_builder()
+            .previous_output(input_out_point1)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(input_out_point2)
+            .build(),
+    ];
+    if error != DexError::OwnerCellMissing {
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(input_out_point3)
+                .build(),
+        )
+    }
+
+    let output_asset_amount = Bytes::from(
+        (1000_0000_0000u128 + 3456_0000_0000u128)
+            .to_le_bytes()
+            .to_vec(),
+    );
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(240_0000_0000u64.pack())
+            .lock(owner_lock.clone())
+            .type_(Some(asset_type).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(400_0000_0000u64.pack())
+            .lock(owner_lock.clone())
+            .build(),
+    ];
+
+    let outputs_data = vec![output_asset_amount, Bytes::new()];
+
+    // the witness of each cancelled order carries the owner lock hash in its lock field
+    let owner_lock_hash = if error == DexError::MarkerNotMatch {
+        Bytes::from(rng.gen::<[u8; 32]>().to_vec())
+    } else {
+        owner_lock.calc_script_hash().as_bytes()
+    };
+    let marker = WitnessArgs::new_builder()
+        .lock(Some(owner_lock_hash).pack())
+        .build()
+        .as_bytes();
+    let marked_orders = match error {
+        DexError::NoOrderMarked => 0,
+        DexError::OneOrderMarked => 1,
+        _ => 2,
+    };
+    let mut witnesses = vec![];
+    for index in 0..inputs.len() {
+        if index < marked_orders {
+            witnesses.push(marker.clone())
+        } else {
+            witnesses.push(Bytes::new())
+        }
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_scoped_cancel_orders_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_scoped_cancel_unmarked_order_error() {
+    let (context, tx) = create_test_context(DexError::OneOrderMarked);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_scoped_cancel_owner_cell_only_error() {
+    let (context, tx) = create_test_context(DexError::NoOrderMarked);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
+}
+
+#[test]
+fn test_dex_scoped_cancel_owner_cell_missing_error() {
+    let (context, tx) = create_test_context(DexError::OwnerCellMissing);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexCancelNotAuthorized);
+}
+
+#[test]
+fn test_dex_scoped_cancel_marker_not_match_error() {
+    let (context, tx) = create_test_context(DexError::MarkerNotMatch);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexCancelNotAuthorized);
+}
@@ -0,0 +1,310 @@
+use dex_errors::DexLockError;
+use dex_types::DexArgsBuilder;
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum DexError {
+    NoError,
+    // the taker does not spend a cell but receives the asset
+    AssetSentToTaker,
+    // the owner cancels the private order
+    OwnerCancel,
+    OtherTaker,
+    // the taker only receives a plain capacity cell instead of the asset
+    TakerOutputWithoutAsset,
+    // the taker only receives one token and another lock keeps the rest of the asset
+    PartialAssetSentToTaker,
+    // two private orders of the same taker send their assets to the taker in two outputs
+    TwoOrdersSentToTaker,
+    // two private orders of the same taker send their assets to the taker in one output
+    TwoOrdersShareTakerOutput,
+}
+
+fn create_test_context(error: DexError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let taker_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let other_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let mut taker_lock_hash = [0u8; 32];
+    taker_lock_hash.copy_from_slice(taker_lock.calc_script_hash().as_slice());
+
+    let total_value = 1234_5678_0000u128;
+    let dex_args = DexArgsBuilder::new(owner_lock.as_slice(), total_value)
+        .taker_lock_hash(taker_lock_hash)
+        .build()
+        .unwrap();
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+    let two_orders = matches!(
+        error,
+        DexError::TwoOrdersSentToTaker | DexError::TwoOrdersShareTakerOutput
+    );
+    // the second order has another price, so it is in another script group
+    let second_value = 2000_0000_0000u128;
+    let second_args = DexArgsBuilder::new(owner_lock.as_slice(), second_value)
+        .taker_lock_hash(taker_lock_hash)
+        .build()
+        .unwrap();
+    let second_lock_script = context
+        .build_script(&dex_out_point, second_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let dex_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(dex_lock_script)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+        asset_data.clone(),
+    );
+
+    // the cell paying the order and the lock receiving the asset
+    let (payer_lock, asset_lock) = match error {
+        DexError::NoError => (taker_lock.clone(), taker_lock.clone()),
+        DexError::AssetSentToTaker
+        | DexError::TwoOrdersSentToTaker
+        | DexError::TwoOrdersShareTakerOutput => (other_lock.clone(), taker_lock.clone()),
+        DexError::OwnerCancel => (owner_lock.clone(), owner_lock.clone()),
+        _ => (other_lock.clone(), other_lock.clone()),
+    };
+    let payer_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000_0000_0000u64.pack())
+            .lock(payer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+
+    let mut inputs = vec![
+        CellInput::new_builder()
+            .previous_output(dex_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(payer_out_point)
+            .build(),
+    ];
+    if two_orders {
+        let second_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(second_lock_script)
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+            asset_data.clone(),
+        );
+        inputs.insert(
+            1,
+            CellInput::new_builder()
+                .previous_output(second_out_point)
+                .build(),
+        );
+    }
+
+    let payment = if error == DexError::OwnerCancel {
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(owner_lock.clone())
+            .type_(Some(asset_type.clone()).pack())
+            .build()
+    } else {
+        CellOutput::new_builder()
+            .capacity((total_value as u64 + dex_capacity).pack())
+            .lock(owner_lock.clone())
+            .build()
+    };
+    let payment_data = if error == DexError::OwnerCancel {
+        asset_data.clone()
+    } else {
+        Bytes::new()
+    };
+    let mut outputs = vec![
+        payment,
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(asset_lock)
+            .type_(Some(asset_type.clone()).pack())
+            .build(),
+    ];
+    let mut outputs_data = vec![payment_data, asset_data.clone()];
+    if two_orders {
+        outputs.insert(
+            1,
+            CellOutput::new_builder()
+                .capacity((second_value as u64 + dex_capacity).pack())
+                .lock(owner_lock.clone())
+                .build(),
+        );
+        outputs_data.insert(1, Bytes::new());
+    }
+    if error == DexError::TwoOrdersSentToTaker {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(taker_lock.clone())
+                .type_(Some(asset_type.clone()).pack())
+                .build(),
+        );
+        outputs_data.push(asset_data);
+    }
+    if error == DexError::PartialAssetSentToTaker {
+        outputs_data[1] = Bytes::from((1000_0000_0000u128 - 1).to_le_bytes().to_vec());
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(dex_capacity.pack())
+                .lock(taker_lock.clone())
+                .type_(Some(asset_type).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(1u128.to_le_bytes().to_vec()));
+    }
+    if error == DexError::TakerOutputWithoutAsset {
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(100_0000_0000u64.pack())
+                .lock(taker_lock)
+                .build(),
+        );
+        outputs_data.push(Bytes::new());
+    }
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_taker_lock_taker_input_success() {
+    let (context, tx) = create_test_context(DexError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_lock_asset_sent_to_taker_success() {
+    let (context, tx) = create_test_context(DexError::AssetSentToTaker);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_lock_owner_cancel_success() {
+    let (context, tx) = create_test_context(DexError::OwnerCancel);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_lock_other_taker_error() {
+    let (context, tx) = create_test_context(DexError::OtherTaker);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TakerNotAllowed);
+}
+
+#[test]
+fn test_dex_taker_lock_output_without_asset_error() {
+    let (context, tx) = create_test_context(DexError::TakerOutputWithoutAsset);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TakerNotAllowed);
+}
+
+#[test]
+fn test_dex_taker_lock_partial_asset_sent_to_taker_error() {
+    let (context, tx) = create_test_context(DexError::PartialAssetSentToTaker);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TakerNotAllowed);
+}
+
+#[test]
+fn test_dex_taker_lock_two_orders_sent_to_taker_success() {
+    let (context, tx) = create_test_context(DexError::TwoOrdersSentToTaker);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_lock_two_orders_share_taker_output_error() {
+    let (context, tx) = create_test_context(DexError::TwoOrdersShareTakerOutput);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::TakerNotAllowed);
+}
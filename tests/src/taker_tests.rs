@@ -1,4 +1,9 @@
-use self::helper::DexArgs;
+// The cell data is built with `Bytes::try_from`, which newer clippy flags because the
+// conversion from a `Vec<u8>` cannot fail
+#![allow(clippy::unnecessary_fallible_conversions)]
+
+use dex_errors::DexLockError;
+use dex_types::DexArgs;
 
 use super::*;
 use ckb_testtool::builtin::ALWAYS_SUCCESS;
@@ -14,23 +19,17 @@ use rand::{thread_rng, Rng};
 
 const MAX_CYCLES: u64 = 70_000_000;
 
-// error numbers
-const LOCK_ARGS_INVALID: i8 = 5;
-const DEX_OWNER_LOCK_NOT_MATCH: i8 = 6;
-const DEX_FT_TOTAL_VALUE_NOT_MATCH: i8 = 7;
-const DEX_NFT_TOTAL_VALUE_NOT_MATCH: i8 = 8;
-const DEX_SETUP_INVALID: i8 = 9;
-const TOTAL_VALUE_OVERFLOW: i8 = 10;
-
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum DexError {
     NoError,
     LockArgsInvalid,
+    LockArgsTrailingBytes,
     DexOwnerLockNotMatch,
     DexFTTotalValueNotMatch,
     DexNFTTotalValueNotMatch,
     DexSetupInvalid,
     TotalValueOverflow,
+    DexVersionUnknown,
 }
 
 fn create_test_context(error: DexError) -> (Context, TransactionView) {
@@ -68,29 +67,31 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
         )
         .expect("script");
 
-    let setup = if error == DexError::DexSetupInvalid {
-        1u8
-    } else if error == DexError::DexNFTTotalValueNotMatch {
+    let setup = if error == DexError::DexSetupInvalid || error == DexError::DexNFTTotalValueNotMatch
+    {
         4u8
     } else {
         0u8
     };
 
-    let receiver_lock: Option<[u8; 32]> = if error == DexError::DexSetupInvalid {
-        Some([0u8; 32])
-    } else {
-        None
-    };
-
     let dex_args1 = DexArgs {
-        owner_lock: owner_lock1.clone(),
+        owner_lock: owner_lock1.as_slice().to_vec(),
         setup,
         total_value: 1234_5678_0000u128,
-        receiver_lock,
+        receiver_lock: None,
         unit_type_hash: None,
+        extension: None,
     };
+    let mut dex_args1_vec = dex_args1.to_vec().unwrap();
+    // the encoder refuses invalid args, so set the setup byte by hand
+    if error == DexError::DexVersionUnknown {
+        dex_args1_vec[owner_lock1.as_slice().len()] |= 0b1111_0000;
+    } else if error == DexError::DexSetupInvalid {
+        // only fungible tokens can be partially filled
+        dex_args1_vec[owner_lock1.as_slice().len()] |= 0b0000_1000;
+    }
     let dex_lock_script1 = context
-        .build_script(&dex_out_point, dex_args1.to_vec().unwrap().into())
+        .build_script(&dex_out_point, dex_args1_vec.into())
         .expect("script");
 
     let total_value = if error == DexError::TotalValueOverflow {
@@ -99,15 +100,18 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
         9_8765_0000_1234u128
     };
     let dex_args2 = DexArgs {
-        owner_lock: owner_lock2.clone(),
+        owner_lock: owner_lock2.as_slice().to_vec(),
         setup: 0u8,
         total_value,
         receiver_lock: None,
         unit_type_hash: None,
+        extension: None,
     };
     let mut dex_args2_vec = dex_args2.to_vec().unwrap();
     if error == DexError::LockArgsInvalid {
         dex_args2_vec.reverse();
+    } else if error == DexError::LockArgsTrailingBytes {
+        dex_args2_vec.push(0);
     }
     let dex_lock_script2 = context
         .build_script(&dex_out_point, dex_args2_vec.into())
@@ -209,7 +213,15 @@ fn test_dex_taker_order_lock_args_error() {
     let (context, tx) = create_test_context(DexError::LockArgsInvalid);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, LOCK_ARGS_INVALID);
+    assert_script_error(err, DexLockError::LockArgsInvalid);
+}
+
+#[test]
+fn test_dex_taker_order_lock_args_trailing_bytes_error() {
+    let (context, tx) = create_test_context(DexError::LockArgsTrailingBytes);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::LockArgsInvalid);
 }
 
 #[test]
@@ -217,7 +229,7 @@ fn test_dex_taker_order_owner_lock_not_match_error() {
     let (context, tx) = create_test_context(DexError::DexOwnerLockNotMatch);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, DEX_OWNER_LOCK_NOT_MATCH);
+    assert_script_error(err, DexLockError::DexOwnerLockNotMatch);
 }
 
 #[test]
@@ -225,7 +237,7 @@ fn test_dex_ft_taker_order_total_value_not_match_error() {
     let (context, tx) = create_test_context(DexError::DexFTTotalValueNotMatch);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, DEX_FT_TOTAL_VALUE_NOT_MATCH);
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
 }
 
 #[test]
@@ -233,7 +245,7 @@ fn test_dex_nft_taker_order_total_value_not_match_error() {
     let (context, tx) = create_test_context(DexError::DexNFTTotalValueNotMatch);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, DEX_NFT_TOTAL_VALUE_NOT_MATCH);
+    assert_script_error(err, DexLockError::DexNFTTotalValueNotMatch);
 }
 
 #[test]
@@ -241,7 +253,15 @@ fn test_dex_taker_order_total_setup_invalid_error() {
     let (context, tx) = create_test_context(DexError::DexSetupInvalid);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, DEX_SETUP_INVALID);
+    assert_script_error(err, DexLockError::DexSetupInvalid);
+}
+
+#[test]
+fn test_dex_taker_order_version_unknown_error() {
+    let (context, tx) = create_test_context(DexError::DexVersionUnknown);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexVersionUnknown);
 }
 
 #[test]
@@ -249,5 +269,154 @@ fn test_dex_taker_order_total_value_overflow_error() {
     let (context, tx) = create_test_context(DexError::TotalValueOverflow);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-    assert_script_error(err, TOTAL_VALUE_OVERFLOW);
+    assert_script_error(err, DexLockError::TotalValueOverflow);
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum IdenticalOrdersError {
+    NoError,
+    SecondOrderNotPaid,
+    SecondOrderUnderpaid,
+}
+
+// Two orders with identical args belong to the same script group, and each of them must be
+// settled by the output at its own index
+fn create_identical_orders_context(error: IdenticalOrdersError) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+    let dex_out_point = context.deploy_cell(dex_bin);
+    let dex_dep = CellDep::new_builder()
+        .out_point(dex_out_point.clone())
+        .build();
+
+    let always_success_code_hash = blake2b_256(ALWAYS_SUCCESS.clone());
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let always_success_dep = CellDep::new_builder()
+        .out_point(always_success_out_point.clone())
+        .build();
+
+    let mut rng = thread_rng();
+    let owner_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+    let buyer_lock = context
+        .build_script(
+            &always_success_out_point,
+            rng.gen::<[u8; 20]>().to_vec().into(),
+        )
+        .expect("script");
+
+    let total_value = 1234_5678_0000u128;
+    let dex_args = DexArgs {
+        owner_lock: owner_lock.as_slice().to_vec(),
+        setup: 0u8,
+        total_value,
+        receiver_lock: None,
+        unit_type_hash: None,
+        extension: None,
+    };
+    let dex_lock_script = context
+        .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
+        .expect("script");
+
+    let asset_type = ScriptBuilder::default()
+        .code_hash(Byte32::from_slice(&always_success_code_hash).unwrap())
+        .hash_type(Byte::from(2u8))
+        .build();
+
+    let dex_capacity = 300_0000_0000u64;
+    let asset_amount = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let inputs = (0..2)
+        .map(|_| {
+            let out_point = context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(dex_capacity.pack())
+                    .lock(dex_lock_script.clone())
+                    .type_(Some(asset_type.clone()).pack())
+                    .build(),
+                asset_amount.clone(),
+            );
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect::<Vec<_>>();
+
+    let payment_capacity = total_value as u64 + dex_capacity;
+    let first_output = CellOutput::new_builder()
+        .capacity(payment_capacity.pack())
+        .lock(owner_lock.clone())
+        .build();
+    let second_output = match error {
+        IdenticalOrdersError::NoError => first_output.clone(),
+        IdenticalOrdersError::SecondOrderNotPaid => CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        IdenticalOrdersError::SecondOrderUnderpaid => CellOutput::new_builder()
+            .capacity((payment_capacity - 1).pack())
+            .lock(owner_lock)
+            .build(),
+    };
+    let outputs = vec![
+        first_output,
+        second_output,
+        CellOutput::new_builder()
+            .capacity(dex_capacity.pack())
+            .lock(buyer_lock)
+            .type_(Some(asset_type).pack())
+            .build(),
+    ];
+
+    let outputs_data = vec![
+        Bytes::new(),
+        Bytes::new(),
+        Bytes::from(2000_0000_0000u128.to_le_bytes().to_vec()),
+    ];
+
+    let mut witnesses = vec![];
+    for _ in 0..inputs.len() {
+        witnesses.push(Bytes::new())
+    }
+
+    let cell_deps = vec![always_success_dep, dex_dep];
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    (context, tx)
+}
+
+#[test]
+fn test_dex_taker_identical_orders_success() {
+    let (context, tx) = create_identical_orders_context(IdenticalOrdersError::NoError);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_identical_orders_second_not_paid_error() {
+    let (context, tx) = create_identical_orders_context(IdenticalOrdersError::SecondOrderNotPaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexOwnerLockNotMatch);
+}
+
+#[test]
+fn test_dex_taker_identical_orders_second_underpaid_error() {
+    let (context, tx) = create_identical_orders_context(IdenticalOrdersError::SecondOrderUnderpaid);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexLockError::DexFTTotalValueNotMatch);
 }
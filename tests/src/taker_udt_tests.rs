@@ -1,4 +1,9 @@
-use self::helper::DexArgs;
+// The cell data is built with `Bytes::try_from`, which newer clippy flags because the
+// conversion from a `Vec<u8>` cannot fail
+#![allow(clippy::unnecessary_fallible_conversions)]
+
+use dex_errors::DexLockError;
+use dex_types::DexArgs;
 
 use super::*;
 use ckb_testtool::builtin::ALWAYS_SUCCESS;
@@ -17,11 +22,13 @@ const MAX_CYCLES: u64 = 70_000_000;
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum DexError {
     NoError,
-    UnitTypeNotMatch = 11,
-    TotalValueNotMatch,
+    UnitTypeNotMatch = DexLockError::UnitTypeNotMatch as i8,
+    TotalValueNotMatch = DexLockError::TotalValueNotMatch as i8,
+    UnitDataLengthNotEnough = DexLockError::UnitDataLengthNotEnough as i8,
 }
 
-fn create_test_context(error: DexError) -> (Context, TransactionView) {
+// With xudt_extension_data, the payment is an xUDT cell with extension data after the amount
+fn create_test_context(error: DexError, xudt_extension_data: bool) -> (Context, TransactionView) {
     // deploy contract
     let mut context = Context::default();
     let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
@@ -35,11 +42,19 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
     let sudt_dep = CellDep::new_builder()
         .out_point(dex_out_point.clone())
         .build();
-    let sudt_type_script = context
-        .build_script(&sudt_out_point, Bytes::from(vec![42]))
-        .expect("script");
-
     let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    // The sUDT script requires exactly 16 bytes of data, so an always success type script plays
+    // the xUDT whose data may be shorter or longer than that
+    let sudt_type_script = if error == DexError::UnitDataLengthNotEnough || xudt_extension_data {
+        context
+            .build_script(&always_success_out_point, Bytes::from(vec![42]))
+            .expect("script")
+    } else {
+        context
+            .build_script(&sudt_out_point, Bytes::from(vec![42]))
+            .expect("script")
+    };
+
     let always_success_dep = CellDep::new_builder()
         .out_point(always_success_out_point.clone())
         .build();
@@ -70,11 +85,12 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
 
     let setup = 0b0000_0010;
     let dex_args = DexArgs {
-        owner_lock: owner_lock.clone(),
+        owner_lock: owner_lock.as_slice().to_vec(),
         setup,
         total_value,
         receiver_lock: None,
         unit_type_hash: Some(temp),
+        extension: None,
     };
     let dex_lock_script = context
         .build_script(&dex_out_point, dex_args.to_vec().unwrap().into())
@@ -132,10 +148,17 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
             .build(),
     ];
 
-    let first_output_data = if error == DexError::TotalValueNotMatch {
-        Bytes::try_from((total_value - 1).to_le_bytes().to_vec()).unwrap()
-    } else {
-        Bytes::try_from(total_value.to_le_bytes().to_vec()).unwrap()
+    let first_output_data = match error {
+        DexError::TotalValueNotMatch => {
+            Bytes::try_from((total_value - 1).to_le_bytes().to_vec()).unwrap()
+        }
+        DexError::UnitDataLengthNotEnough => Bytes::from(total_value.to_le_bytes()[..8].to_vec()),
+        _ if xudt_extension_data => {
+            let mut data = total_value.to_le_bytes().to_vec();
+            data.extend([0x55u8; 36]);
+            Bytes::from(data)
+        }
+        _ => Bytes::try_from(total_value.to_le_bytes().to_vec()).unwrap(),
     };
 
     let outputs_data = vec![
@@ -166,7 +189,7 @@ fn create_test_context(error: DexError) -> (Context, TransactionView) {
 
 #[test]
 fn test_dex_taker_order_success() {
-    let (context, tx) = create_test_context(DexError::NoError);
+    let (context, tx) = create_test_context(DexError::NoError, false);
     // run
     let cycles = context
         .verify_tx(&tx, MAX_CYCLES)
@@ -176,7 +199,7 @@ fn test_dex_taker_order_success() {
 
 #[test]
 fn test_dex_taker_order_unit_type_not_match_error() {
-    let (context, tx) = create_test_context(DexError::UnitTypeNotMatch);
+    let (context, tx) = create_test_context(DexError::UnitTypeNotMatch, false);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
     assert_script_error(err, DexError::UnitTypeNotMatch as i8);
@@ -184,8 +207,26 @@ fn test_dex_taker_order_unit_type_not_match_error() {
 
 #[test]
 fn test_dex_taker_order_total_value_not_match_error() {
-    let (context, tx) = create_test_context(DexError::TotalValueNotMatch);
+    let (context, tx) = create_test_context(DexError::TotalValueNotMatch, false);
     // run
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
     assert_script_error(err, DexError::TotalValueNotMatch as i8);
 }
+
+#[test]
+fn test_dex_taker_order_xudt_extension_data_success() {
+    let (context, tx) = create_test_context(DexError::NoError, true);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dex_taker_order_unit_data_length_not_enough_error() {
+    let (context, tx) = create_test_context(DexError::UnitDataLengthNotEnough, false);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DexError::UnitDataLengthNotEnough as i8);
+}
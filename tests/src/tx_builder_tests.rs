@@ -0,0 +1,787 @@
+use dex_tx_builder::{
+    buy_orders, cancel_orders, list_order, reprice_order, BuildError, DexLockConfig, LiveCell,
+    Order,
+};
+use dex_types::{since, AcceptedUnit, AmountFormat, DexArgsBuilder};
+
+use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use rand::{thread_rng, Rng};
+
+const MAX_CYCLES: u64 = 70_000_000;
+const FEE: u64 = 1000;
+
+struct TestEnv {
+    context:          Context,
+    config:           DexLockConfig,
+    always_success:   OutPoint,
+    asset_type:       Script,
+    sudt_type_script: Script,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let mut context = Context::default();
+        let dex_bin: Bytes = Loader::default().load_binary("dex-lock");
+        let dex_out_point = context.deploy_cell(dex_bin);
+        let dex_lock_template = context
+            .build_script(&dex_out_point, Bytes::new())
+            .expect("script");
+        let config = DexLockConfig {
+            code_hash: dex_lock_template.code_hash(),
+            hash_type: ScriptHashType::Type,
+            cell_dep:  CellDep::new_builder().out_point(dex_out_point).build(),
+        };
+
+        let sudt_bin: Bytes = Loader::default().load_binary("sudt");
+        let sudt_out_point = context.deploy_cell(sudt_bin);
+        let sudt_type_script = context
+            .build_script(&sudt_out_point, Bytes::from(vec![42]))
+            .expect("script");
+
+        let always_success = context.deploy_cell(ALWAYS_SUCCESS.clone());
+        let asset_type = ScriptBuilder::default()
+            .code_hash(Byte32::from_slice(&blake2b_256(ALWAYS_SUCCESS.clone())).unwrap())
+            .hash_type(Byte::from(2u8))
+            .build();
+
+        TestEnv {
+            context,
+            config,
+            always_success,
+            asset_type,
+            sudt_type_script,
+        }
+    }
+
+    fn random_lock(&mut self) -> Script {
+        let mut rng = thread_rng();
+        self.context
+            .build_script(&self.always_success, rng.gen::<[u8; 20]>().to_vec().into())
+            .expect("script")
+    }
+
+    fn create_live_cell(
+        &mut self,
+        capacity: u64,
+        lock: Script,
+        type_: Option<Script>,
+        data: Bytes,
+    ) -> LiveCell {
+        let output = CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .type_(type_.pack())
+            .build();
+        let out_point = self.context.create_cell(output.clone(), data.clone());
+        LiveCell {
+            out_point,
+            output,
+            data,
+        }
+    }
+
+    // Creates the cell at the output of a transaction as a live cell
+    fn live_cell_of(&mut self, tx: &TransactionView, index: usize) -> LiveCell {
+        let output = tx.outputs().get(index).unwrap();
+        let data = tx.outputs_data().get(index).unwrap().raw_data();
+        let lock = output.lock();
+        let type_ = output.type_().to_opt();
+        self.create_live_cell(output.capacity().unpack(), lock, type_, data)
+    }
+
+    fn verify(&mut self, tx: TransactionView) -> Result<u64, ckb_testtool::ckb_error::Error> {
+        let tx = self.context.complete_tx(tx);
+        self.context.verify_tx(&tx, MAX_CYCLES)
+    }
+}
+
+fn lock_hash(lock: &Script) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(lock.calc_script_hash().as_slice());
+    hash
+}
+
+#[test]
+fn test_tx_builder_list_and_buy_ckb_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let asset_data = Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec());
+    let asset = env.create_live_cell(
+        200_0000_0000,
+        seller_lock.clone(),
+        Some(env.asset_type.clone()),
+        asset_data,
+    );
+    let seller_cell = env.create_live_cell(500_0000_0000, seller_lock.clone(), None, Bytes::new());
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .build()
+        .unwrap();
+    let listing = list_order(
+        &env.config,
+        &asset,
+        &args,
+        &[seller_cell],
+        &seller_lock,
+        FEE,
+    )
+    .unwrap();
+    assert!(env
+        .config
+        .is_dex_lock(&listing.outputs().get(0).unwrap().lock()));
+    env.verify(listing.clone()).expect("pass verification");
+
+    let order = Order::new(env.live_cell_of(&listing, 0));
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(
+        &env.config,
+        &[order.clone()],
+        &[buyer_cell],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap();
+    let payment = tx.outputs().get(0).unwrap();
+    assert_eq!(payment.lock(), seller_lock);
+    assert_eq!(
+        Unpack::<u64>::unpack(&payment.capacity()),
+        1234_5678_0000 + order.cell.capacity()
+    );
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_list_and_buy_committed_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let asset = env.create_live_cell(
+        200_0000_0000,
+        seller_lock.clone(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    );
+    let seller_cell = env.create_live_cell(500_0000_0000, seller_lock.clone(), None, Bytes::new());
+    let commitment = asset.asset_commitment();
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .nft()
+        .asset(commitment.type_hash, commitment.data_hash)
+        .build()
+        .unwrap();
+    let listing = list_order(
+        &env.config,
+        &asset,
+        &args,
+        &[seller_cell],
+        &seller_lock,
+        FEE,
+    )
+    .unwrap();
+    env.verify(listing.clone()).expect("pass verification");
+
+    // The listed cell matches the commitment in its own args
+    let order = Order::new(env.live_cell_of(&listing, 0));
+    assert_eq!(order.cell.asset_commitment(), commitment);
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(&env.config, &[order], &[buyer_cell], &buyer_lock, FEE).unwrap();
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_udt_and_nft_orders() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let receiver_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+    let unit_type_hash = lock_hash(&env.sudt_type_script);
+
+    let udt_args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .unit_type_hash(unit_type_hash)
+        .build()
+        .unwrap();
+    let udt_order_cell = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&udt_args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let nft_args = DexArgsBuilder::new(seller_lock.as_slice(), 800_0000_0000)
+        .receiver_lock(lock_hash(&receiver_lock))
+        .nft()
+        .build()
+        .unwrap();
+    let nft_order_cell = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&nft_args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    );
+    let orders = vec![
+        Order::new(udt_order_cell),
+        Order::with_receiver_lock(nft_order_cell, receiver_lock.clone()),
+    ];
+
+    let buyer_udt = env.create_live_cell(
+        300_0000_0000,
+        buyer_lock.clone(),
+        Some(env.sudt_type_script.clone()),
+        Bytes::from(4000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(
+        &env.config,
+        &orders,
+        &[buyer_udt, buyer_cell],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap();
+    assert_eq!(tx.outputs().get(0).unwrap().lock(), seller_lock);
+    assert_eq!(tx.outputs().get(1).unwrap().lock(), receiver_lock);
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_royalty_orders() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let royalty_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+    let unit_type_hash = lock_hash(&env.sudt_type_script);
+
+    let nft_args = DexArgsBuilder::new(seller_lock.as_slice(), 800_0000_0000)
+        .nft()
+        .royalty(lock_hash(&royalty_lock), 250)
+        .build()
+        .unwrap();
+    let nft_order_cell = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&nft_args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    );
+    let udt_args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .unit_type_hash(unit_type_hash)
+        .royalty(lock_hash(&royalty_lock), 500)
+        .build()
+        .unwrap();
+    let udt_order_cell = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&udt_args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let orders = vec![
+        Order::new(nft_order_cell).share_lock(royalty_lock.clone()),
+        Order::new(udt_order_cell).share_lock(royalty_lock.clone()),
+    ];
+
+    let buyer_udt = env.create_live_cell(
+        300_0000_0000,
+        buyer_lock.clone(),
+        Some(env.sudt_type_script.clone()),
+        Bytes::from(4000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let err = buy_orders(
+        &env.config,
+        &[Order::new(orders[0].cell.clone())],
+        &[buyer_cell.clone()],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap_err();
+    assert_eq!(err, BuildError::ShareLockNotFound);
+
+    let tx = buy_orders(
+        &env.config,
+        &orders,
+        &[buyer_udt, buyer_cell],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap();
+    // The royalty follows each payment and a buyer cell fills the input between the orders
+    assert_eq!(tx.outputs().get(0).unwrap().lock(), seller_lock);
+    assert_eq!(tx.outputs().get(1).unwrap().lock(), royalty_lock);
+    assert_eq!(tx.outputs().get(2).unwrap().lock(), seller_lock);
+    assert_eq!(tx.outputs().get(3).unwrap().lock(), royalty_lock);
+    assert_eq!(
+        tx.outputs_data().get(3).unwrap().raw_data(),
+        Bytes::from((1234_5678_0000u128 * 500 / 10000).to_le_bytes().to_vec())
+    );
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_private_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let taker_lock = env.random_lock();
+    let other_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .taker_lock_hash(lock_hash(&taker_lock))
+        .build()
+        .unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    ));
+
+    let other_cell = env.create_live_cell(5000_0000_0000, other_lock.clone(), None, Bytes::new());
+    let err = buy_orders(
+        &env.config,
+        &[order.clone()],
+        &[other_cell],
+        &other_lock,
+        FEE,
+    )
+    .unwrap_err();
+    assert_eq!(err, BuildError::TakerNotAllowed);
+
+    let taker_cell = env.create_live_cell(5000_0000_0000, taker_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(&env.config, &[order], &[taker_cell], &taker_lock, FEE).unwrap();
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_dutch_auction_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+        .nft()
+        .dutch_auction(
+            200_0000_0000,
+            since::absolute_block_number(1000),
+            since::absolute_block_number(2000),
+        )
+        .build()
+        .unwrap();
+    let order_cell = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    );
+    let order = Order::new(order_cell).since(since::absolute_block_number(1500));
+
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(&env.config, &[order], &[buyer_cell], &buyer_lock, FEE).unwrap();
+    let since: u64 = tx.inputs().get(0).unwrap().since().unpack();
+    assert_eq!(since, since::absolute_block_number(1500));
+    let capacity: u64 = tx.outputs().get(0).unwrap().capacity().unpack();
+    assert_eq!(capacity, 600_0000_0000);
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_bundle_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+        .bundle([1u8; 32], 2)
+        .build()
+        .unwrap();
+    let orders: Vec<Order> = (0..2)
+        .map(|_| {
+            Order::new(env.create_live_cell(
+                300_0000_0000,
+                env.config.lock_script(&args).unwrap(),
+                Some(env.asset_type.clone()),
+                Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+            ))
+        })
+        .collect();
+
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(&env.config, &orders, &[buyer_cell], &buyer_lock, FEE).unwrap();
+    // One payment settles the bundle and pays back the capacity of both members
+    assert_eq!(tx.outputs().get(0).unwrap().lock(), seller_lock);
+    let capacity: u64 = tx.outputs().get(0).unwrap().capacity().unpack();
+    assert_eq!(capacity, 1600_0000_0000);
+    assert_eq!(tx.outputs().get(1).unwrap().lock(), buyer_lock);
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_bundle_and_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let bundle_args = DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+        .nft()
+        .bundle([1u8; 32], 2)
+        .build()
+        .unwrap();
+    let mut orders: Vec<Order> = (0..2)
+        .map(|_| {
+            Order::new(env.create_live_cell(
+                300_0000_0000,
+                env.config.lock_script(&bundle_args).unwrap(),
+                Some(env.asset_type.clone()),
+                Bytes::from(vec![7u8; 40]),
+            ))
+        })
+        .collect();
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 800_0000_0000)
+        .nft()
+        .build()
+        .unwrap();
+    orders.push(Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![8u8; 40]),
+    )));
+
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(&env.config, &orders, &[buyer_cell], &buyer_lock, FEE).unwrap();
+    // The order after the bundle is paid at the index of its input, and the output at the index
+    // of the second member takes the asset of the first one
+    let capacity: u64 = tx.outputs().get(0).unwrap().capacity().unpack();
+    assert_eq!(capacity, 1000_0000_0000);
+    assert_eq!(tx.outputs().get(1).unwrap().lock(), buyer_lock);
+    assert_eq!(tx.outputs().get(2).unwrap().lock(), seller_lock);
+    let capacity: u64 = tx.outputs().get(2).unwrap().capacity().unpack();
+    assert_eq!(capacity, 800_0000_0000);
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_alt_unit_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+    let unit_type_hash = lock_hash(&env.sudt_type_script);
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+        .nft()
+        .alt_unit(Some(unit_type_hash), 50_0000_0000)
+        .build()
+        .unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    ));
+
+    let buyer_udt = env.create_live_cell(
+        300_0000_0000,
+        buyer_lock.clone(),
+        Some(env.sudt_type_script.clone()),
+        Bytes::from(4000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let other_unit = AcceptedUnit {
+        unit_type_hash: Some([1u8; 32]),
+        total_value:    50_0000_0000,
+    };
+    let err = buy_orders(
+        &env.config,
+        &[order.clone().pay_in(other_unit)],
+        &[buyer_cell.clone()],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap_err();
+    assert_eq!(err, BuildError::UnitNotAccepted);
+
+    let order = order.pay_in(args.accepted_units()[1]);
+    let tx = buy_orders(
+        &env.config,
+        &[order],
+        &[buyer_udt, buyer_cell],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap();
+    assert_eq!(
+        tx.outputs_data().get(0).unwrap().raw_data(),
+        Bytes::from(50_0000_0000u128.to_le_bytes().to_vec())
+    );
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_big_endian_unit_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let royalty_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+    // An always success type script plays a token with a big-endian amount
+    let unit_type = env
+        .asset_type
+        .clone()
+        .as_builder()
+        .args(Bytes::from(vec![9u8]).pack())
+        .build();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+        .nft()
+        .unit_type_hash(lock_hash(&unit_type))
+        .royalty(lock_hash(&royalty_lock), 500)
+        .amount_format(AmountFormat::UdtBigEndian)
+        .build()
+        .unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(vec![7u8; 40]),
+    ))
+    .share_lock(royalty_lock);
+
+    let buyer_udt = env.create_live_cell(
+        300_0000_0000,
+        buyer_lock.clone(),
+        Some(unit_type.clone()),
+        Bytes::from(4000_0000_0000u128.to_be_bytes().to_vec()),
+    );
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let tx = buy_orders(
+        &env.config,
+        &[order],
+        &[buyer_udt, buyer_cell],
+        &buyer_lock,
+        FEE,
+    )
+    .unwrap();
+    // The payment, the royalty and the change of the buyer are all big-endian
+    let amounts = [950_0000_0000u128, 50_0000_0000, 3000_0000_0000];
+    for (index, amount) in [0, 1, 3].into_iter().zip(amounts) {
+        assert_eq!(
+            tx.outputs_data().get(index).unwrap().raw_data(),
+            Bytes::from(amount.to_be_bytes().to_vec())
+        );
+    }
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // Counted cells cannot be split from the buyer cells
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 2)
+        .nft()
+        .unit_type_hash(lock_hash(&unit_type))
+        .amount_format(AmountFormat::CellCount)
+        .build()
+        .unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::new(),
+    ));
+    let err = buy_orders(&env.config, &[order], &[], &buyer_lock, FEE).unwrap_err();
+    assert_eq!(err, BuildError::AmountFormatNotSupported);
+
+    // Neither can the layout read by an amount reader
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 2)
+        .nft()
+        .unit_type_hash(lock_hash(&unit_type))
+        .amount_reader([14u8; 32], 1)
+        .build()
+        .unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::new(),
+    ));
+    let err = buy_orders(&env.config, &[order], &[], &buyer_lock, FEE).unwrap_err();
+    assert_eq!(err, BuildError::AmountFormatNotSupported);
+}
+
+#[test]
+fn test_tx_builder_cancel_orders() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .build()
+        .unwrap();
+    let orders = (0..2)
+        .map(|_| {
+            let lock = env.config.lock_script(&args).unwrap();
+            env.create_live_cell(
+                300_0000_0000,
+                lock,
+                Some(env.asset_type.clone()),
+                Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+            )
+        })
+        .collect::<Vec<_>>();
+    let seller_cell = env.create_live_cell(100_0000_0000, seller_lock.clone(), None, Bytes::new());
+    let tx = cancel_orders(&env.config, &orders, &[seller_cell], &seller_lock, FEE).unwrap();
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_reprice_order() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .scoped_cancel()
+        .build()
+        .unwrap();
+    let order = env.create_live_cell(
+        300_0000_0000,
+        env.config.lock_script(&args).unwrap(),
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let seller_cell = env.create_live_cell(100_0000_0000, seller_lock.clone(), None, Bytes::new());
+    let tx = reprice_order(&env.config, &order, 1000_0000_0000, &[seller_cell], FEE).unwrap();
+    // The order keeps its index and only its total_value changes
+    let repriced = Order::new(env.live_cell_of(&tx, 0))
+        .args(&env.config)
+        .unwrap();
+    assert_eq!(repriced.total_value, 1000_0000_0000);
+    assert_eq!(repriced.owner_lock, args.owner_lock);
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_scoped_cancel_orders() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .scoped_cancel()
+        .build()
+        .unwrap();
+    let lock = env.config.lock_script(&args).unwrap();
+    let order = env.create_live_cell(
+        300_0000_0000,
+        lock,
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    let seller_cell = env.create_live_cell(100_0000_0000, seller_lock.clone(), None, Bytes::new());
+    let tx = cancel_orders(&env.config, &[order], &[seller_cell], &seller_lock, FEE).unwrap();
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(1).unwrap().raw_data()).unwrap();
+    assert_eq!(
+        witness.lock().to_opt().unwrap().raw_data(),
+        seller_lock.calc_script_hash().as_bytes()
+    );
+    let cycles = env.verify(tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_tx_builder_buy_without_owner_cell_cancel_error() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let other_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .build()
+        .unwrap();
+    let lock = env.config.lock_script(&args).unwrap();
+    let order = env.create_live_cell(
+        300_0000_0000,
+        lock,
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    );
+    // Cancelling with a cell that is not locked by the owner is just an unpaid take
+    let other_cell = env.create_live_cell(100_0000_0000, other_lock.clone(), None, Bytes::new());
+    let tx = cancel_orders(&env.config, &[order], &[other_cell], &other_lock, FEE).unwrap();
+    assert!(env.verify(tx).is_err());
+}
+
+#[test]
+fn test_tx_builder_buy_bid_and_auction_error() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let cases = [
+        DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+            .nft()
+            .bid(lock_hash(&env.asset_type)),
+        DexArgsBuilder::new(seller_lock.as_slice(), 1000_0000_0000)
+            .nft()
+            .english_auction(since::absolute_block_number(1000)),
+    ];
+    for builder in cases {
+        let args = builder.build().unwrap();
+        let order = Order::new(env.create_live_cell(
+            300_0000_0000,
+            env.config.lock_script(&args).unwrap(),
+            Some(env.asset_type.clone()),
+            Bytes::from(vec![7u8; 40]),
+        ));
+        let buyer_cell =
+            env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+        let err = buy_orders(&env.config, &[order], &[buyer_cell], &buyer_lock, FEE).unwrap_err();
+        assert_eq!(err, BuildError::OrderNotSupported);
+    }
+}
+
+#[test]
+fn test_tx_builder_insufficient_error() {
+    let mut env = TestEnv::new();
+    let seller_lock = env.random_lock();
+    let buyer_lock = env.random_lock();
+
+    let args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .unit_type_hash(lock_hash(&env.sudt_type_script))
+        .build()
+        .unwrap();
+    let lock = env.config.lock_script(&args).unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        lock,
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    ));
+
+    let buyer_cell = env.create_live_cell(5000_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let err = buy_orders(&env.config, &[order], &[buyer_cell], &buyer_lock, FEE).unwrap_err();
+    assert_eq!(err, BuildError::InsufficientUdt);
+
+    let ckb_args = DexArgsBuilder::new(seller_lock.as_slice(), 1234_5678_0000)
+        .build()
+        .unwrap();
+    let lock = env.config.lock_script(&ckb_args).unwrap();
+    let order = Order::new(env.create_live_cell(
+        300_0000_0000,
+        lock,
+        Some(env.asset_type.clone()),
+        Bytes::from(1000_0000_0000u128.to_le_bytes().to_vec()),
+    ));
+    let buyer_cell = env.create_live_cell(500_0000_0000, buyer_lock.clone(), None, Bytes::new());
+    let err = buy_orders(&env.config, &[order], &[buyer_cell], &buyer_lock, FEE).unwrap_err();
+    assert_eq!(err, BuildError::InsufficientCapacity);
+}